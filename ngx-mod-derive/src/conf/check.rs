@@ -0,0 +1,176 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{Expr, ExprLit, Lit, LitStr};
+
+use crate::util::{find_ngx_mod, find_ngx_rt};
+
+use super::{Directive, Set};
+
+impl<'a> Directive<'a> {
+    /// Returns the post handler which validates the value after it has been set,
+    /// if the setter calls the post handler of the command.
+    pub fn post(&self) -> Option<TokenStream> {
+        let ngx_rt = find_ngx_rt();
+        let handler = self.post_handler()?;
+
+        self.set().calls_post().then(|| {
+            quote! {
+                #handler
+
+                const POST: #ngx_rt ::ffi::ngx_conf_post_t = #ngx_rt ::ffi::ngx_conf_post_t {
+                    post_handler: Some(post),
+                };
+
+                &POST as *const _ as *mut _
+            }
+        })
+    }
+
+    /// Returns the setter of the directive, which is wrapped to validate the value after it has been set,
+    /// if the setter doesn't call the post handler, e.g. `set = enum_values`, `set = bitmask` or a custom setter.
+    pub fn setter(&self) -> TokenStream {
+        let ngx_rt = find_ngx_rt();
+        let set = self.set();
+
+        match self.post_handler() {
+            Some(handler) if !set.calls_post() => quote! { {
+                #handler
+
+                unsafe extern "C" fn set_validated(
+                    cf: *mut #ngx_rt ::ffi::ngx_conf_t,
+                    cmd: *mut #ngx_rt ::ffi::ngx_command_t,
+                    conf: *mut ::std::ffi::c_void,
+                ) -> *mut ::std::ffi::c_char {
+                    let rv = #set (cf, cmd, conf);
+
+                    if rv != #ngx_rt ::core::NGX_CONF_OK {
+                        return rv;
+                    }
+
+                    post(
+                        cf,
+                        ::std::ptr::null_mut(),
+                        conf.cast::<u8>().add((*cmd).offset).cast(),
+                    )
+                }
+
+                set_validated
+            } },
+            _ => quote! { #set },
+        }
+    }
+
+    /// Returns the post handler which validates the value with the checks of the directive.
+    fn post_handler(&self) -> Option<TokenStream> {
+        if !self.args.has_checks() && self.conflicts.is_empty() {
+            return None;
+        }
+
+        let ngx_rt = find_ngx_rt();
+        let ngx_mod = find_ngx_mod();
+        let struct_name = self.struct_name;
+        let field_name = &self.name;
+        let (ty, value_ty) = self.parsed_types();
+
+        let deprecated = self.args.deprecated.as_ref().map(|arg| {
+            let note = &arg.value;
+
+            quote! { #ngx_rt ::core::conf::deprecated(cf, #note); }
+        });
+
+        let conflicts = self.conflicts.iter().map(|(field, name)| {
+            quote! { #ngx_rt ::core::conf::check_conflict(&conf. #field, #name)?; }
+        });
+
+        let range = self.args.range.as_ref().map(|arg| {
+            let range = &arg.value;
+
+            quote! { #ngx_rt ::core::conf::check_range(value, &( #range ))?; }
+        });
+
+        let min_size = self.args.min_size.as_ref().map(|arg| {
+            let desc = &arg.value;
+            let min = parse_size(desc);
+
+            quote! { #ngx_rt ::core::conf::check_min_size(value, #min, #desc)?; }
+        });
+
+        let one_of = self.args.one_of.as_ref().map(|arg| {
+            let values = arg.value.elems.iter().map(|elem| match elem {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => s,
+                _ => abort!(elem, "expect a string literal"),
+            });
+
+            quote! { #ngx_rt ::core::conf::check_one_of(value, &[ #( #values ),* ])?; }
+        });
+
+        let checks = quote! { #range #min_size #one_of };
+        let is_option = quote! { #ty }.to_string() != quote! { #value_ty }.to_string();
+        let checks = if !is_option || checks.is_empty() {
+            checks
+        } else {
+            quote! {
+                if let Some(value) = value.as_ref() {
+                    #checks
+                }
+            }
+        };
+
+        Some(quote! {
+            unsafe extern "C" fn post(
+                cf: *mut #ngx_rt ::ffi::ngx_conf_t,
+                _post: *mut ::std::ffi::c_void,
+                field: *mut ::std::ffi::c_void,
+            ) -> *mut ::std::ffi::c_char {
+                #ngx_rt ::core::conf::validate::< #struct_name, #ty >(
+                    cf,
+                    field,
+                    #ngx_mod ::memoffset::offset_of!( #struct_name , #field_name ),
+                    |cf, conf, value| {
+                        let _ = (cf, conf, value);
+
+                        #deprecated
+                        #( #conflicts )*
+                        #checks
+
+                        Ok(())
+                    },
+                )
+            }
+        })
+    }
+}
+
+impl Set {
+    /// Returns `true` if the setter calls the post handler with the field.
+    fn calls_post(&self) -> bool {
+        use Set::*;
+
+        match self {
+            Flag | Str | Number | Size | Offset | MSec | Seconds => true,
+            Value(_) | Multi(_) | Block(_) | Parse(..) => true,
+            #[cfg(feature = "serde")]
+            Deserialize(..) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Parses the size like `ngx_parse_size`, e.g. `4k` or `1m`.
+fn parse_size(lit: &LitStr) -> usize {
+    let s = lit.value();
+    let (n, scale) = match s.as_bytes().last() {
+        Some(b'k' | b'K') => (&s[..s.len() - 1], 1024),
+        Some(b'm' | b'M') => (&s[..s.len() - 1], 1024 * 1024),
+        Some(b'g' | b'G') => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+        _ => (s.as_str(), 1),
+    };
+
+    n.parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .unwrap_or_else(|| abort!(lit, "invalid size, expect e.g. `4096`, `4k` or `1m`"))
+}
@@ -2,11 +2,14 @@ use case::CaseExt;
 use proc_macro2::TokenStream;
 use proc_macro_error::abort;
 use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{parse_quote, Expr, GenericArgument, Ident, Path, Stmt, Type, TypePath};
+use syn::{
+    parse_quote, AngleBracketedGenericArguments, Expr, GenericArgument, Ident, Path, PathArguments,
+    Stmt, Type, TypePath, TypeReference,
+};
 
 use crate::util::{find_ngx_mod, find_ngx_rt};
 
-use super::{FieldArgs, Offset, Set, StructArgs};
+use super::{Args, FieldArgs, Offset, Set, StructArgs};
 
 pub struct Directive<'a> {
     pub struct_args: &'a StructArgs,
@@ -14,6 +17,12 @@ pub struct Directive<'a> {
     pub args: FieldArgs,
     pub name: Ident,
     pub ty: Type,
+    /// The fields and names of the conflicting directives.
+    pub conflicts: Vec<(Ident, String)>,
+    /// The doc comments of the field.
+    pub doc: String,
+    /// The default value in the reference documentation.
+    pub default: Option<String>,
 }
 
 impl<'a> ToTokens for Directive<'a> {
@@ -37,15 +46,21 @@ impl<'a> ToTokens for Directive<'a> {
             .map(|ty| quote! { #ty })
             .chain(self.args.args().into_iter().map(|args| quote! { #args }));
         let set = self.set();
+        let setter = self.setter();
         let assertions: Option<Stmt> = self.assertions().map(|expr| parse_quote! { #expr ; });
+        let validate = self.post();
         let post = if matches!(set, Set::Enum | Set::BitMask) {
             if let Some(p) = self.args.values.as_ref().map(|arg| &arg.value) {
                 quote! { #ngx_rt ::core::conf::enum_values( & #p ).as_ptr().cast() }
+            } else if let Some(ty) = self.enum_type() {
+                quote! { <#ty as #ngx_rt ::core::conf::NgxEnum>::VALUES.as_ptr() as *mut _ }
             } else {
                 abort! {
                     self.name.span(), "missing enum values"
                 }
             }
+        } else if let Some(post) = validate {
+            quote! { { #post } }
         } else {
             quote! { ::std::ptr::null_mut() }
         };
@@ -57,7 +72,7 @@ impl<'a> ToTokens for Directive<'a> {
                 set: {
                     #assertions
 
-                    Some( #set )
+                    Some( #setter )
                 },
                 conf: #conf_off as usize,
                 offset: #ngx_mod ::memoffset::offset_of!( #struct_name , #field_name ) as usize,
@@ -68,7 +83,7 @@ impl<'a> ToTokens for Directive<'a> {
 }
 
 impl<'a> Directive<'a> {
-    fn name(&self) -> String {
+    pub fn name(&self) -> String {
         self.args
             .name
             .as_ref()
@@ -76,35 +91,90 @@ impl<'a> Directive<'a> {
             .unwrap_or_else(|| self.name.to_string().to_snake())
     }
 
-    fn set(&self) -> Set {
+    pub fn set(&self) -> Set {
         if let Some(p) = self.args.set.as_ref().map(|p| &p.value) {
-            p.get_ident()
-                .and_then(|i| i.to_string().to_lowercase().as_str().parse().ok())
-                .unwrap_or_else(|| Set::Setter(p.clone()))
-        } else {
-            match self.ty {
-                Type::Path(TypePath {
-                    ref qself,
-                    ref path,
-                }) if qself.is_none() => {
-                    if let Some(ident) = path.get_ident() {
-                        match ident.to_string().as_str() {
-                            "Flag" => Set::Flag,
-                            "Str" => Set::Str,
-                            "isize" => Set::Number,
-                            "usize" => Set::Size,
-                            "MSec" => Set::MSec,
-                            "Sec" => Set::Seconds,
-                            "Bufs" => Set::Buffers,
-                            _ => abort! { self.ty, "unexpected field type: {:?}", path },
-                        }
-                    } else {
-                        abort! { self.ty, "unexpected field type: {:?}", path }
-                    }
+            let name = p.get_ident().map(|i| i.to_string().to_lowercase());
+
+            match name.as_deref() {
+                Some("parse") if self.takes_multiple_args() => self.deserialize(p),
+                Some("parse") => {
+                    let (field, value) = self.parsed_types();
+
+                    Set::Parse(field, value)
                 }
-                _ => abort! { self.ty, "unknown directive type: {:?}", self.ty },
+                Some("deserialize") => self.deserialize(p),
+                _ => name
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| Set::Setter(p.clone())),
             }
+        } else if self.args.multi.is_some() || self.args.block.value() {
+            let mut ty = self.ty.clone();
+
+            strip_type_lifetime(&mut ty);
+
+            if self.args.block.value() {
+                Set::Block(ty)
+            } else {
+                Set::Multi(ty)
+            }
+        } else {
+            infer_set(&self.ty).unwrap_or_else(|| match last_ident(&self.ty).as_deref() {
+                Some("usize") => abort! {
+                    self.ty, "unable to infer the setter of `usize` field, add `set = size` or `set = num`"
+                },
+                Some("bool") => abort! {
+                    self.ty, "`off` can't be distinguished from an unset `bool` field, use `conf::Flag` or `Option<bool>`"
+                },
+                Some(ty @ ("String" | "Duration")) => abort! {
+                    self.ty, "an empty `{}` field can't be distinguished from an unset one, use `Option<{}>`", ty, ty
+                },
+                _ => abort! {
+                    self.ty, "unable to infer the setter of field type, please specify it with `set = ...`"
+                },
+            })
+        }
+    }
+
+    /// Returns the `T` of the `Enum<T>` or `BitMask<T>` field.
+    fn enum_type(&self) -> Option<&Type> {
+        match last_ident(&self.ty).as_deref() {
+            Some("Enum" | "BitMask") => generic_arg(&self.ty),
+            _ => None,
+        }
+    }
+
+    fn takes_multiple_args(&self) -> bool {
+        self.args
+            .args()
+            .iter()
+            .any(|args| !matches!(args, Args::None | Args::Take1 | Args::Block | Args::Flag))
+    }
+
+    /// Returns the field type and the parsed value type, e.g. `T` of the `Option<T>` field.
+    pub fn parsed_types(&self) -> (Type, Type) {
+        let mut field = self.ty.clone();
+
+        strip_type_lifetime(&mut field);
+
+        let value = match last_ident(&field).as_deref() {
+            Some("Option") => generic_arg(&field).cloned(),
+            _ => None,
         }
+        .unwrap_or_else(|| field.clone());
+
+        (field, value)
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize(&self, _p: &Path) -> Set {
+        let (field, value) = self.parsed_types();
+
+        Set::Deserialize(field, value)
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn deserialize(&self, p: &Path) -> Set {
+        abort! { p, "multiple arguments are deserialized with `serde`, which support is disabled" }
     }
 
     pub fn assertions(&self) -> Option<Expr> {
@@ -116,6 +186,66 @@ impl<'a> Directive<'a> {
     }
 }
 
+/// Infers the setter from the field type.
+///
+/// The optional owned Rust types, e.g. `Option<bool>`, `Option<String>` or `Option<Duration>`,
+/// are set with `set_value_slot`, and `usize` requires an explicit `set = size` or `set = num`.
+fn infer_set(ty: &Type) -> Option<Set> {
+    match ty {
+        Type::Reference(TypeReference { elem, .. }) => match last_ident(elem)?.as_str() {
+            "ArrayRef" => match last_ident(generic_arg(elem)?)?.as_str() {
+                "Str" => Some(Set::StrArray),
+                "KeyValue" => Some(Set::KeyValue),
+                _ => None,
+            },
+            _ => None,
+        },
+        Type::Path(TypePath { qself: None, .. }) => match last_ident(ty)?.as_str() {
+            "Flag" => Some(Set::Flag),
+            "Str" => Some(Set::Str),
+            "isize" => Some(Set::Number),
+            "MSec" => Some(Set::MSec),
+            "Sec" => Some(Set::Seconds),
+            "Bufs" => Some(Set::Buffers),
+            "Enum" => Some(Set::Enum),
+            "BitMask" => Some(Set::BitMask),
+            "Option" => match generic_arg(ty)? {
+                inner @ Type::Reference(_) => infer_set(inner),
+                inner => match last_ident(inner)?.as_str() {
+                    "bool" | "String" | "Duration" => Some(Set::Value(ty.clone())),
+                    _ => None,
+                },
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn last_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            path.segments.last().map(|s| s.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn generic_arg(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => match path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) => {
+                args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn strip_type_lifetime(ty: &mut Type) {
     match ty {
         Type::Reference(syn::TypeReference {
@@ -0,0 +1,235 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, MetaNameValue};
+
+use crate::util::find_ngx_rt;
+
+use super::{Args, Directive, Set};
+
+/// The reference documentation of the directive, see `ngx_rt::core::conf::DirectiveDoc`.
+pub struct Doc {
+    pub name: String,
+    pub syntax: String,
+    pub default: Option<String>,
+    pub contexts: Vec<&'static str>,
+    pub doc: String,
+}
+
+impl<'a> Directive<'a> {
+    pub fn doc(&self) -> Doc {
+        let name = self.name();
+        let mut contexts = vec![];
+
+        for ty in self
+            .struct_args
+            .scope
+            .iter()
+            .flat_map(|scope| scope.types.iter())
+        {
+            if let Some(ctx) = ty.context() {
+                if !contexts.contains(&ctx) {
+                    contexts.push(ctx);
+                }
+            }
+        }
+
+        if contexts.is_empty() {
+            contexts.push("any");
+        }
+
+        Doc {
+            syntax: self.syntax(&name),
+            default: self.default.as_ref().map(|v| format!("{} {};", name, v)),
+            name,
+            contexts,
+            doc: self.doc.clone(),
+        }
+    }
+
+    /// Returns the syntax of the directive, e.g. `name time [size ...];` or `name { ... }`.
+    fn syntax(&self, name: &str) -> String {
+        let args = self.args.args();
+        let block = args.contains(&Args::Block);
+        let counts = args
+            .iter()
+            .filter_map(|args| match args {
+                Args::Block => None,
+                Args::Flag => Some(1),
+                args => Some(args.clone() as usize),
+            })
+            .collect::<Vec<_>>();
+        let min = counts.iter().copied().min().unwrap_or_default();
+        let max = counts.iter().copied().max().unwrap_or_default();
+
+        let placeholder = self.placeholder();
+        let arg = |i: usize| {
+            if max > 1 && placeholder == "value" {
+                format!("{}{}", placeholder, i + 1)
+            } else {
+                placeholder.clone()
+            }
+        };
+
+        let mut words = vec![name.to_string()];
+
+        words.extend((0..min).map(arg));
+
+        match max - min {
+            0 => {}
+            1 => words.push(format!("[{}]", arg(min))),
+            _ => words.push(format!("[{} ...]", arg(min))),
+        }
+
+        if block {
+            words.push("{ ... }".to_string());
+
+            words.join(" ")
+        } else {
+            words.join(" ") + ";"
+        }
+    }
+
+    /// Returns the placeholder of the argument, e.g. `on | off`, `time` or `size`.
+    fn placeholder(&self) -> String {
+        if let Some(arg) = self.args.one_of.as_ref() {
+            return arg
+                .value
+                .elems
+                .iter()
+                .flat_map(|elem| match elem {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }) => Some(s.value()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+        }
+
+        match self.set() {
+            Set::Flag => "on | off",
+            Set::Number => "number",
+            Set::Size | Set::Offset => "size",
+            Set::MSec | Set::Seconds => "time",
+            Set::Value(_) => {
+                let (_, ty) = self.parsed_types();
+                let ty = quote! { #ty }.to_string();
+
+                if ty == "bool" {
+                    "on | off"
+                } else if ty.ends_with("Duration") {
+                    "time"
+                } else {
+                    "value"
+                }
+            }
+            _ => "value",
+        }
+        .to_string()
+    }
+}
+
+impl Doc {
+    /// Returns the JSON object of the directive in a single line.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"name\":{},\"syntax\":{},\"default\":{},\"context\":[{}],\"doc\":{}}}\n",
+            json_str(&self.name),
+            json_str(&self.syntax),
+            self.default.as_deref().map_or("null".to_string(), json_str),
+            self.contexts
+                .iter()
+                .map(|ctx| json_str(ctx))
+                .collect::<Vec<_>>()
+                .join(","),
+            json_str(&self.doc),
+        )
+    }
+}
+
+impl ToTokens for Doc {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ngx_rt = find_ngx_rt();
+        let Doc {
+            name,
+            syntax,
+            default,
+            contexts,
+            doc,
+        } = self;
+        let default = match default {
+            Some(default) => quote! { Some( #default ) },
+            None => quote! { None },
+        };
+
+        tokens.append_all(quote! {
+            #ngx_rt ::core::conf::DirectiveDoc {
+                name: #name,
+                syntax: #syntax,
+                default: #default,
+                contexts: &[ #( #contexts ),* ],
+                doc: #doc,
+            }
+        })
+    }
+}
+
+/// Returns the doc comments of the attributes.
+pub fn doc_comments(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(ref s),
+                        ..
+                    }),
+                ..
+            }) => Some(s.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Returns the literal of the default value in the directive syntax, e.g. `60`, `5s` or `on`.
+pub fn default_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(s) => Some(s.value()),
+            Lit::Int(n) => Some(n.base10_digits().to_string()),
+            Lit::Float(n) => Some(n.base10_digits().to_string()),
+            Lit::Bool(b) => Some(if b.value { "on" } else { "off" }.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the JSON string of `s`.
+pub fn json_str(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+
+    json
+}
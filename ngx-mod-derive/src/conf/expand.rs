@@ -1,13 +1,16 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_error::abort;
 use quote::quote;
 use syn::{
-    parse_quote, Block, Data, DataStruct, DeriveInput, Fields, FieldsNamed, Ident, ItemImpl,
+    parse_quote, spanned::Spanned, Block, Data, DataStruct, DeriveInput, Fields, FieldsNamed,
+    Ident, ItemImpl, LitByteStr,
 };
 
-use crate::{conf::r#struct::DefaultValue, extract, util::find_ngx_rt};
+use crate::{
+    conf::r#struct::DefaultValue, extract, merge::FieldArgs as MergeArgs, util::find_ngx_rt,
+};
 
-use super::{Directive, FieldArgs, StructArgs};
+use super::{default_value, doc_comments, Directive, FieldArgs, StructArgs};
 
 pub fn expand(input: DeriveInput) -> TokenStream {
     let DeriveInput {
@@ -22,7 +25,7 @@ pub fn expand(input: DeriveInput) -> TokenStream {
     let struct_name: &Ident = &ident;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let (field_names, directives) = if let Data::Struct(DataStruct {
+    let (field_names, mut directives) = if let Data::Struct(DataStruct {
         fields: Fields::Named(FieldsNamed { named, .. }),
         ..
     }) = data
@@ -38,14 +41,30 @@ pub fn expand(input: DeriveInput) -> TokenStream {
                     let syn::Field {
                         attrs, ident, ty, ..
                     } = f;
+                    let doc = doc_comments(&attrs);
+                    let (merge, _) = extract::args::<MergeArgs, _>(attrs.clone(), "merge");
                     let (args, _) = extract::args::<FieldArgs, _>(attrs, "directive");
 
-                    args.map(|args| Directive {
-                        struct_args: &struct_args,
-                        struct_name,
-                        args,
-                        name: ident.expect("name"),
-                        ty,
+                    args.map(|args| {
+                        let default = match args.default.as_ref() {
+                            Some(arg) => Some(default_value(&arg.value).unwrap_or_else(|| {
+                                abort!(arg.value.span(), "default value should be a literal")
+                            })),
+                            None => merge
+                                .and_then(|merge| merge.default)
+                                .and_then(|arg| default_value(&arg.value)),
+                        };
+
+                        Directive {
+                            struct_args: &struct_args,
+                            struct_name,
+                            args,
+                            name: ident.expect("name"),
+                            ty,
+                            conflicts: vec![],
+                            doc,
+                            default,
+                        }
                     })
                 })
                 .collect::<Vec<_>>(),
@@ -57,13 +76,35 @@ pub fn expand(input: DeriveInput) -> TokenStream {
         )
     };
 
+    for i in 0..directives.len() {
+        if let Some(other) = directives[i]
+            .args
+            .conflicts_with
+            .as_ref()
+            .map(|arg| &arg.value)
+        {
+            let Some(j) = directives
+                .iter()
+                .position(|d| d.name() == other.value())
+                .or_else(|| directives.iter().position(|d| d.name == other.value()))
+            else {
+                abort!(other.span(), "unknown directive `{}`", other.value())
+            };
+            let (name, other_name) = (directives[i].name.clone(), directives[j].name.clone());
+            let (dir, other_dir) = (directives[i].name(), directives[j].name());
+
+            directives[i].conflicts.push((other_name, other_dir));
+            directives[j].conflicts.push((name, dir));
+        }
+    }
+
     let ngx_rt = find_ngx_rt();
 
     let impl_default: Option<ItemImpl> = struct_args.default_value().map(|v| {
         let block: Block = match v {
             DefaultValue::Unset => {
                 parse_quote!{ {
-                    <Self as #ngx_rt ::core::conf::Unset>::UNSET
+                    <Self as #ngx_rt ::core::conf::Unset>::unset()
                 } }
             }
             DefaultValue::Zeroed => {
@@ -90,6 +131,12 @@ pub fn expand(input: DeriveInput) -> TokenStream {
                         #( #field_names : #ngx_rt ::core::conf::unset(), )*
                     };
 
+                    fn unset() -> Self {
+                        Self {
+                            #( #field_names : #ngx_rt ::core::conf::Unset::unset(), )*
+                        }
+                    }
+
                     fn is_unset(&self) -> bool {
                         #( #ngx_rt ::core::conf::Unset::is_unset( & self. #field_names ) ) &&*
                     }
@@ -98,6 +145,15 @@ pub fn expand(input: DeriveInput) -> TokenStream {
         });
 
     let n = directives.len();
+    let docs = directives.iter().map(|d| d.doc()).collect::<Vec<_>>();
+    let doc = LitByteStr::new(
+        docs.iter()
+            .map(|doc| doc.to_json())
+            .collect::<String>()
+            .as_bytes(),
+        Span::call_site(),
+    );
+    let doc_len = doc.value().len();
 
     let impl_unsafe_conf: ItemImpl = parse_quote! {
         impl #impl_generics #ngx_rt ::core::UnsafeConf for #struct_name #ty_generics #where_clause {
@@ -106,14 +162,93 @@ pub fn expand(input: DeriveInput) -> TokenStream {
             const COMMANDS: Self::Commands = [
                 #( #directives ),*
             ];
+
+            type Doc = [u8; #doc_len];
+
+            const DOC: Self::Doc = * #doc;
         }
     };
 
+    let defaults = directives
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| d.args.default.is_some())
+        .collect::<Vec<_>>();
+    let default_idxs = defaults.iter().map(|(i, _)| i);
+    let default_names = defaults.iter().map(|(_, d)| &d.name).collect::<Vec<_>>();
+    let default_values = defaults.iter().map(|(_, d)| d.default.as_deref());
+    let block_names = directives
+        .iter()
+        .filter(|d| d.args.block.span.is_some())
+        .map(|d| &d.name)
+        .collect::<Vec<_>>();
+
+    let init_defaults = (!default_names.is_empty() || !block_names.is_empty()).then(|| {
+        quote! {
+            fn init_defaults(
+                &mut self,
+                cf: & #ngx_rt ::core::ConfRef,
+            ) -> ::std::result::Result<(), #ngx_rt ::Error> {
+                #(
+                    if #ngx_rt ::core::conf::Unset::is_unset(&self. #default_names) {
+                        unsafe {
+                            #ngx_rt ::core::conf::set_default(
+                                cf,
+                                &<Self as #ngx_rt ::core::UnsafeConf>::COMMANDS[ #default_idxs ],
+                                self as *mut Self as *mut ::std::ffi::c_void,
+                                #default_values,
+                            )?;
+                        }
+                    }
+                )*
+                #(
+                    #ngx_rt ::core::conf::ConfBlock::init_defaults(&mut self. #block_names, cf)?;
+                )*
+
+                Ok(())
+            }
+        }
+    });
+
+    let (required_names, required_directives) = directives
+        .iter()
+        .filter(|d| d.args.required.value())
+        .map(|d| (&d.name, d.name()))
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    let check_required = (!required_names.is_empty()).then(|| {
+        quote! {
+            fn check_required(&self) -> ::std::result::Result<(), #ngx_rt ::Error> {
+                #(
+                    if #ngx_rt ::core::conf::Unset::is_unset(&self. #required_names) {
+                        return Err(#ngx_rt ::Error::ConfigError(::std::ffi::CString::new(
+                            concat!("\"", #required_directives, "\" directive is not set"),
+                        )?));
+                    }
+                )*
+
+                Ok(())
+            }
+        }
+    });
+
     let impl_conf_ext: ItemImpl = parse_quote! {
         impl #impl_generics #ngx_rt ::core::ConfExt for #struct_name #ty_generics #where_clause {
             fn commands() -> #ngx_rt ::core::Cmds<'static> {
                 #ngx_rt ::core::Cmds::from( & <Self as #ngx_rt ::core::UnsafeConf>::COMMANDS[..])
             }
+
+            fn directives() -> &'static [#ngx_rt ::core::conf::DirectiveDoc] {
+                const DIRECTIVES: [#ngx_rt ::core::conf::DirectiveDoc; #n] = [
+                    #( #docs ),*
+                ];
+
+                &DIRECTIVES
+            }
+
+            #init_defaults
+
+            #check_required
         }
     };
 
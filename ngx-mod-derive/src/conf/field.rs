@@ -6,7 +6,7 @@ use structmeta::{Flag, NameArgs, NameValue, StructMeta};
 use syn::{
     parse::{Parse, ParseStream},
     spanned::Spanned,
-    Expr, ExprLit, ExprRange, Lit, LitInt, LitStr, Path, RangeLimits, Token,
+    Expr, ExprArray, ExprLit, ExprRange, Ident, Lit, LitInt, LitStr, Path, RangeLimits, Token,
 };
 
 use super::{Args, Offset};
@@ -20,8 +20,17 @@ pub struct FieldArgs {
     pub block: Flag,
     #[merge(strategy = merge_flag)]
     pub flag: Flag,
+    pub multi: Option<NameArgs<Option<Ident>>>,
     pub set: Option<NameValue<Path>>,
     pub values: Option<NameValue<Path>>,
+    pub range: Option<NameValue<ExprRange>>,
+    pub min_size: Option<NameValue<LitStr>>,
+    pub one_of: Option<NameValue<ExprArray>>,
+    #[merge(strategy = merge_flag)]
+    pub required: Flag,
+    pub deprecated: Option<NameValue<LitStr>>,
+    pub conflicts_with: Option<NameValue<LitStr>>,
+    pub default: Option<NameValue<Expr>>,
 }
 
 fn merge_flag(left: &mut Flag, right: Flag) {
@@ -80,6 +89,30 @@ impl FieldArgs {
         })
     }
 
+    /// Returns the merge strategy of the repeatable directive, e.g. `inherit`, `append` or `replace`.
+    pub fn multi_merge(&self) -> Option<&'static str> {
+        self.multi.as_ref().map(|arg| match arg.args.as_ref() {
+            Some(ident) => match ident.to_string().as_str() {
+                "inherit" => "inherit",
+                "append" => "append",
+                "replace" => "replace",
+                _ => abort! {
+                    ident.span(), "unknown merge strategy `{}`, expected `inherit`, `append` or `replace`", ident
+                },
+            },
+            None => "inherit",
+        })
+    }
+
+    /// Returns `true` if the directive value should be validated by the post handler.
+    pub fn has_checks(&self) -> bool {
+        self.range.is_some()
+            || self.min_size.is_some()
+            || self.one_of.is_some()
+            || self.deprecated.is_some()
+            || self.conflicts_with.is_some()
+    }
+
     pub fn args(&self) -> Vec<Args> {
         let mut args = self.args.as_ref().map_or_else(
             || vec![Args::None],
@@ -1,5 +1,7 @@
 mod args;
+mod check;
 mod directive;
+mod doc;
 mod expand;
 mod field;
 mod off;
@@ -9,6 +11,7 @@ mod ty;
 
 pub use self::args::Args;
 pub use self::directive::Directive;
+pub use self::doc::{default_value, doc_comments, json_str};
 pub use self::expand::expand;
 pub use self::field::FieldArgs;
 pub use self::off::Offset;
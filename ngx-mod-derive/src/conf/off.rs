@@ -50,8 +50,8 @@ impl Offset {
 
         cfg_if! {
             if #[cfg(feature = "mail")] {
-                if conf_types.contains(&Type::MailSever) {
-                    return Some(Offset::MailSever)
+                if conf_types.contains(&Type::MailServer) {
+                    return Some(Offset::MailServer)
                 } else if conf_types.contains(&Type::MailMain) {
                     return Some(Offset::MailMain)
                 }
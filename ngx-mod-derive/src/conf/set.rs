@@ -28,6 +28,12 @@ pub enum Set {
     HttpTypes,
     #[cfg(feature = "mail")]
     MailCaps,
+    Value(Type),
+    Multi(Type),
+    Block(Type),
+    Parse(Type, Type),
+    #[cfg(feature = "serde")]
+    Deserialize(Type, Type),
     Setter(Path),
 }
 
@@ -71,7 +77,7 @@ impl ToTokens for Set {
         let set: syn::Path = match self {
             Flag => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_flag_slot },
             Str => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_str_slot },
-            StrArray => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_str_array_slot },
+            StrArray => parse_quote! { #ngx_rt ::core::conf::set_str_array_slot },
             KeyValue => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_keyval_slot },
             Number => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_num_slot },
             Size => parse_quote! { #ngx_rt ::ffi::ngx_conf_set_size_slot },
@@ -89,6 +95,16 @@ impl ToTokens for Set {
             HttpTypes => parse_quote! { #ngx_rt ::ffi::ngx_http_types_slot },
             #[cfg(feature = "mail")]
             MailCaps => parse_quote! { #ngx_rt ::ffi::ngx_mail_capabilities },
+            Value(ty) => parse_quote! { #ngx_rt ::core::conf::set_value_slot::< #ty > },
+            Multi(ty) => parse_quote! { #ngx_rt ::core::conf::set_multi_slot::< #ty > },
+            Block(ty) => parse_quote! { #ngx_rt ::core::conf::set_block_slot::< #ty > },
+            Parse(field, value) => {
+                parse_quote! { #ngx_rt ::core::conf::set_parse_slot::< #field, #value > }
+            }
+            #[cfg(feature = "serde")]
+            Deserialize(field, value) => {
+                parse_quote! { #ngx_rt ::core::conf::set_deserialize_slot::< #field, #value > }
+            }
             Setter(path) => parse_quote! { #path },
         };
 
@@ -129,7 +145,13 @@ impl Set {
             HttpTypes => {
                 Some(parse_quote! { #assert_eq_size!( #ty, * mut #ngx_rt ::ffi::ngx_array_t ) })
             }
-            Setter(_) => None,
+            #[cfg(feature = "mail")]
+            MailCaps => {
+                Some(parse_quote! { #assert_eq_size!( #ty, * mut #ngx_rt ::ffi::ngx_array_t ) })
+            }
+            Value(_) | Multi(_) | Block(_) | Parse(..) | Setter(_) => None,
+            #[cfg(feature = "serde")]
+            Deserialize(..) => None,
         }
     }
 }
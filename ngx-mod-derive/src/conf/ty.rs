@@ -88,7 +88,7 @@ impl Parse for Type {
                         _ => Err(Error::new(p.span(), "unknown `stream` directive type")),
                     }
                 } else {
-                    Err(Error::new(p.span(), "`stream` support is disabled"));
+                    Err(Error::new(p.span(), "`stream` support is disabled"))
                 }
             }
         } else if name.starts_with("mail") {
@@ -114,7 +114,7 @@ impl Parse for Type {
                 if #[cfg(feature = "event")] {
                     Ok(Event)
                 } else {
-                    Err(Error::new(p.span(), "`event` support is disabled"));
+                    Err(Error::new(p.span(), "`event` support is disabled"))
                 }
             }
         } else {
@@ -123,6 +123,43 @@ impl Parse for Type {
     }
 }
 
+impl Type {
+    /// Returns the context of the directive in the reference documentation.
+    pub fn context(&self) -> Option<&'static str> {
+        use Type::*;
+
+        match self {
+            Main => Some("main"),
+            Any => Some("any"),
+            Direct => None,
+            #[cfg(feature = "event")]
+            Event => Some("events"),
+            #[cfg(feature = "http")]
+            HttpMain => Some("http"),
+            #[cfg(feature = "http")]
+            HttpServer => Some("server"),
+            #[cfg(feature = "http")]
+            HttpLocation => Some("location"),
+            #[cfg(feature = "http")]
+            HttpUpstream => Some("upstream"),
+            #[cfg(feature = "http")]
+            HttpServerIf | HttpLocationIf => Some("if"),
+            #[cfg(feature = "http")]
+            HttpLimitExcept => Some("limit_except"),
+            #[cfg(feature = "stream")]
+            StreamMain => Some("stream"),
+            #[cfg(feature = "stream")]
+            StreamServer => Some("server"),
+            #[cfg(feature = "stream")]
+            StreamUpstream => Some("upstream"),
+            #[cfg(feature = "mail")]
+            MailMain => Some("mail"),
+            #[cfg(feature = "mail")]
+            MailServer => Some("server"),
+        }
+    }
+}
+
 impl ToTokens for Type {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         use Type::*;
@@ -133,7 +170,8 @@ impl ToTokens for Type {
             Main => quote! { #ngx_rt ::ffi::NGX_CONF_MAIN },
             Any => quote! { #ngx_rt ::ffi::NGX_CONF_ANY },
             Direct => quote! { #ngx_rt ::ffi::NGX_CONF_DIRECT },
-            Event => quote! { #ngx_rt ::ffi::NGX_CONF_EVENT },
+            #[cfg(feature = "event")]
+            Event => quote! { #ngx_rt ::ffi::NGX_EVENT_CONF },
             #[cfg(feature = "http")]
             HttpMain => quote! { #ngx_rt ::ffi::NGX_HTTP_MAIN_CONF },
             #[cfg(feature = "http")]
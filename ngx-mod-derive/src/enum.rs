@@ -0,0 +1,83 @@
+use merge::Merge;
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use structmeta::{NameValue, StructMeta};
+use syn::{Data, DataEnum, DeriveInput, Fields, LitStr};
+
+use crate::{extract, util::find_ngx_rt};
+
+#[derive(Clone, Debug, Default, Merge, StructMeta)]
+pub struct VariantArgs {
+    pub rename: Option<NameValue<LitStr>>,
+}
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = input;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let Data::Enum(DataEnum { variants, .. }) = data else {
+        abort!(ident.span(), "NgxEnum can only be derived for enum")
+    };
+
+    let ngx_rt = find_ngx_rt();
+
+    let (names, variants) = variants
+        .into_iter()
+        .map(|v| {
+            if !matches!(v.fields, Fields::Unit) {
+                abort!(v.ident.span(), "NgxEnum only support unit variants")
+            }
+
+            let (args, _) = extract::args::<VariantArgs, _>(v.attrs, "ngx");
+            let name = args
+                .and_then(|args| args.rename)
+                .map(|arg| arg.value.value())
+                .unwrap_or_else(|| v.ident.to_string().to_lowercase());
+
+            (name, v.ident)
+        })
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    quote! {
+        impl #impl_generics #ngx_rt ::core::conf::NgxEnum for #ident #ty_generics #where_clause {
+            const VALUES: &'static [#ngx_rt ::ffi::ngx_conf_enum_t] = &[
+                #(
+                    #ngx_rt ::ffi::ngx_conf_enum_t {
+                        name: #ngx_rt ::ngx_str!( #names ),
+                        value: Self:: #variants as #ngx_rt ::ffi::ngx_uint_t,
+                    },
+                )*
+                #ngx_rt ::ffi::ngx_conf_enum_t {
+                    name: #ngx_rt ::ngx_str!(),
+                    value: 0,
+                },
+            ];
+
+            fn from_value(value: #ngx_rt ::ffi::ngx_uint_t) -> Option<Self> {
+                #(
+                    if value == Self:: #variants as #ngx_rt ::ffi::ngx_uint_t {
+                        return Some(Self:: #variants);
+                    }
+                )*
+
+                None
+            }
+
+            fn value(self) -> #ngx_rt ::ffi::ngx_uint_t {
+                self as #ngx_rt ::ffi::ngx_uint_t
+            }
+
+            fn name(self) -> &'static str {
+                match self {
+                    #( Self:: #variants => #names, )*
+                }
+            }
+        }
+    }
+}
@@ -3,9 +3,15 @@ use proc_macro_error::proc_macro_error;
 use syn::parse_macro_input;
 
 mod conf;
+mod r#enum;
 mod extract;
+mod merge;
 mod module;
+#[cfg(feature = "http")]
+mod phase;
 mod util;
+#[cfg(feature = "http")]
+mod var;
 
 #[proc_macro_error]
 #[proc_macro_derive(Module, attributes(module))]
@@ -26,3 +32,47 @@ pub fn derive_conf(item: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+#[proc_macro_error]
+#[proc_macro_derive(Merge, attributes(merge))]
+pub fn derive_merge(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+
+    let expanded = merge::expand(input);
+
+    expanded.into()
+}
+
+#[proc_macro_error]
+#[proc_macro_derive(NgxEnum, attributes(ngx))]
+pub fn derive_ngx_enum(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+
+    let expanded = r#enum::expand(input);
+
+    expanded.into()
+}
+
+#[cfg(feature = "http")]
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn phase_handler(attr: TokenStream, handler: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as phase::Args);
+    let handler = parse_macro_input!(handler as syn::ItemFn);
+
+    let expanded = phase::expand(args, handler);
+
+    expanded.into()
+}
+
+#[cfg(feature = "http")]
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn variable(attr: TokenStream, handler: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as var::Args);
+    let handler = parse_macro_input!(handler as syn::ItemFn);
+
+    let expanded = var::expand(args, handler);
+
+    expanded.into()
+}
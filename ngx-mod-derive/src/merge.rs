@@ -0,0 +1,121 @@
+use merge::Merge;
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use structmeta::{Flag, NameValue, StructMeta};
+use syn::{Data, DataStruct, DeriveInput, Expr, Fields, FieldsNamed, Path};
+
+use crate::{
+    conf::FieldArgs as DirectiveArgs,
+    extract,
+    util::{find_ngx_mod, find_ngx_rt},
+};
+
+#[derive(Clone, Debug, Default, Merge, StructMeta)]
+pub struct FieldArgs {
+    pub strategy: Option<NameValue<Path>>,
+    pub default: Option<NameValue<Expr>>,
+    #[merge(strategy = merge_flag)]
+    pub skip: Flag,
+}
+
+fn merge_flag(left: &mut Flag, right: Flag) {
+    if left.span.is_none() {
+        left.span = right.span;
+    }
+}
+
+/// The strategies provided by `ngx_mod::merge`, which can be specified without the path.
+const STRATEGIES: &[&str] = &[
+    "unset",
+    "skip",
+    "nested",
+    "overwrite_false",
+    "overwrite_true",
+    "overwrite_empty",
+    "inherit",
+    "append",
+    "replace",
+];
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = input;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let Data::Struct(DataStruct {
+        fields: Fields::Named(FieldsNamed { named, .. }),
+        ..
+    }) = data
+    else {
+        abort!(
+            ident.span(),
+            "merge can only be derived for structure with named fields"
+        )
+    };
+
+    let ngx_rt = find_ngx_rt();
+    let ngx_mod = find_ngx_mod();
+
+    let fields = named.into_iter().map(|f| {
+        let name = f.ident.expect("name");
+        let (directive, _) = extract::args::<DirectiveArgs, _>(f.attrs.clone(), "directive");
+        let (args, _) = extract::args::<FieldArgs, _>(f.attrs, "merge");
+        let args = args.unwrap_or_default();
+
+        if args.skip.value() {
+            return quote! {};
+        }
+
+        let strategy = match args.strategy.as_ref().map(|arg| &arg.value) {
+            Some(p) => match p.get_ident() {
+                Some(ident) if STRATEGIES.contains(&ident.to_string().as_str()) => {
+                    quote! { #ngx_mod ::merge:: #ident }
+                }
+                _ => quote! { #p },
+            },
+            None => {
+                let strategy = format_ident!(
+                    "{}",
+                    directive
+                        .as_ref()
+                        .and_then(|args| args.multi_merge())
+                        .unwrap_or("unset")
+                );
+
+                quote! { #ngx_mod ::merge:: #strategy }
+            }
+        };
+
+        let default = args.default.as_ref().map(|arg| {
+            let value = &arg.value;
+
+            quote! {
+                if #ngx_rt ::core::Unset::is_unset(&self. #name) {
+                    self. #name = #value;
+                }
+            }
+        });
+
+        quote! {
+            #strategy (&mut self. #name, &prev. #name)?;
+            #default
+        }
+    });
+
+    quote! {
+        impl #impl_generics #ngx_mod ::Merge for #ident #ty_generics #where_clause {
+            type Error = #ngx_mod ::merge::MergeError;
+
+            fn merge(&mut self, prev: &Self) -> Result<(), Self::Error> {
+                #( #fields )*
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -6,10 +6,11 @@ use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 use structmeta::{NameValue, StructMeta};
 use syn::{
     parse::{Parse, ParseStream},
-    parse_quote, Error, Ident, ItemImpl, ItemStatic,
+    parse_quote, Error, Ident, ItemImpl, ItemStatic, LitByteStr,
 };
 
 use crate::{
+    conf::json_str,
     extract,
     util::{find_ngx_mod, find_ngx_rt},
 };
@@ -185,15 +186,101 @@ pub fn expand(input: syn::DeriveInput) -> TokenStream {
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    let (ty_name, conf_tys) = match &module_ty {
+        Type::Core(_) => (
+            "core",
+            vec![quote! { <#ident as #ngx_mod ::core::Module>::Conf }],
+        ),
+        Type::Conf(_) => ("conf", vec![]),
+        #[cfg(feature = "event")]
+        Type::Event(_) => (
+            "event",
+            vec![quote! { <#ident as #ngx_mod ::event::Module>::Conf }],
+        ),
+        #[cfg(feature = "http")]
+        Type::Http(_) => (
+            "http",
+            vec![
+                quote! { <#ident as #ngx_mod ::http::Module>::MainConf },
+                quote! { <#ident as #ngx_mod ::http::Module>::SrvConf },
+                quote! { <#ident as #ngx_mod ::http::Module>::LocConf },
+            ],
+        ),
+        #[cfg(feature = "mail")]
+        Type::Mail(_) => (
+            "mail",
+            vec![
+                quote! { <#ident as #ngx_mod ::mail::Module>::MainConf },
+                quote! { <#ident as #ngx_mod ::mail::Module>::SrvConf },
+            ],
+        ),
+        #[cfg(feature = "stream")]
+        Type::Stream(_) => (
+            "stream",
+            vec![
+                quote! { <#ident as #ngx_mod ::stream::Module>::MainConf },
+                quote! { <#ident as #ngx_mod ::stream::Module>::SrvConf },
+            ],
+        ),
+    };
+
+    let ngx_module_doc_name = format_ident!("{}_doc", &mod_name);
+    let header = LitByteStr::new(
+        format!(
+            "{{\"module\":{},\"type\":{}}}\n",
+            json_str(&mod_name),
+            json_str(ty_name)
+        )
+        .as_bytes(),
+        Span::call_site(),
+    );
+    let header_len = header.value().len();
+    let n = conf_tys.len();
+
+    let ngx_module_doc: ItemStatic = if conf_tys.is_empty() {
+        parse_quote! {
+            #[no_mangle]
+            pub static #ngx_module_doc_name: [u8; #header_len] = * #header;
+        }
+    } else {
+        parse_quote! {
+            #[no_mangle]
+            pub static #ngx_module_doc_name: [u8;
+                #header_len #( + <#conf_tys as #ngx_rt ::core::UnsafeConf>::DOC.len() )*
+            ] = unsafe {
+                #ngx_mod ::const_concat!(
+                    * #header,
+                    #( <#conf_tys as #ngx_rt ::core::UnsafeConf>::DOC ),*
+                )
+            };
+        }
+    };
+
     let impl_module_metadata: ItemImpl = parse_quote! {
         impl #impl_generics #ngx_mod ::ModuleMetadata for #ident #ty_generics #where_clause {
             fn module() -> &'static #ngx_rt ::core::ModuleRef {
                 unsafe { #ngx_rt ::foreign_types::ForeignTypeRef::from_ptr(&mut #ngx_module_name as *mut _) }
             }
 
+            fn crate_name() -> &'static str {
+                ::std::env!("CARGO_CRATE_NAME")
+            }
+
             fn commands() -> #ngx_rt ::core::Cmds<'static> {
                 #ngx_rt ::core::Cmds::from(unsafe { & #ngx_module_cmds_name [..#ngx_module_cmds_name.len() - 1] })
             }
+
+            fn directives() -> Vec<#ngx_rt ::core::conf::DirectiveDoc> {
+                let directives: [&[#ngx_rt ::core::conf::DirectiveDoc]; #n] = [
+                    #( <#conf_tys as #ngx_rt ::core::ConfExt>::directives() ),*
+                ];
+
+                directives.concat()
+            }
+
+            fn doc() -> &'static str {
+                ::std::str::from_utf8(& #ngx_module_doc_name).expect("doc")
+            }
         }
     };
 
@@ -238,6 +325,34 @@ pub fn expand(input: syn::DeriveInput) -> TokenStream {
                     };
                 }),
             ),
+            #[cfg(feature = "event")]
+            Type::Event(_) => (
+                Some(parse_quote! {
+                    #[no_mangle]
+                    static #ngx_module_ctx_name: #ngx_rt ::ffi::ngx_event_module_t = #ngx_rt ::ffi::ngx_event_module_t {
+                        name: {
+                            static NAME: #ngx_rt ::ffi::ngx_str_t = #ngx_rt ::ngx_str!( #mod_name );
+
+                            &NAME as *const _ as *mut _
+                        },
+                        create_conf: Some(<#ident as #ngx_mod ::event::UnsafeModule>::create_conf),
+                        init_conf: Some(<#ident as #ngx_mod ::event::UnsafeModule>::init_conf),
+                        actions: <#ident as #ngx_mod ::event::Module>::ACTIONS,
+                    };
+                }),
+                Some(parse_quote! {
+                    #[no_mangle]
+                    static mut #ngx_module_cmds_name: [#ngx_rt ::ffi::ngx_command_t;
+                        <<#ident as #ngx_mod ::event::Module>::Conf as #ngx_rt ::core::UnsafeConf>::COMMANDS.len() +
+                    1] = unsafe {
+                        #ngx_mod ::const_concat!(
+                            <<#ident as #ngx_mod ::event::Module> :: Conf as #ngx_rt ::core::UnsafeConf>::COMMANDS,
+                            [ #ngx_rt ::ngx_command!() ]
+                        )
+                    };
+                }),
+            ),
+            #[cfg(feature = "http")]
             Type::Http(_) => (
                 Some(parse_quote! {
                     #[no_mangle]
@@ -268,6 +383,7 @@ pub fn expand(input: syn::DeriveInput) -> TokenStream {
                     };
                 }),
             ),
+            #[cfg(feature = "stream")]
             Type::Stream(_) => (
                 Some(parse_quote! {
                     #[no_mangle]
@@ -294,9 +410,47 @@ pub fn expand(input: syn::DeriveInput) -> TokenStream {
                     };
                 }),
             ),
+            #[cfg(feature = "mail")]
+            Type::Mail(_) => (
+                Some(parse_quote! {
+                    #[no_mangle]
+                    static #ngx_module_ctx_name: #ngx_rt ::ffi::ngx_mail_module_t = #ngx_rt ::ffi::ngx_mail_module_t {
+                        protocol: ::std::ptr::null_mut(),
+                        create_main_conf: Some(<#ident as #ngx_mod ::mail::UnsafeModule>::create_main_conf),
+                        init_main_conf: Some(<#ident as #ngx_mod ::mail::UnsafeModule>::init_main_conf),
+                        create_srv_conf: Some(<#ident as #ngx_mod ::mail::UnsafeModule>::create_srv_conf),
+                        merge_srv_conf: Some(<#ident as #ngx_mod ::mail::UnsafeModule>::merge_srv_conf),
+                    };
+                }),
+                Some(parse_quote! {
+                    #[no_mangle]
+                    static mut #ngx_module_cmds_name: [#ngx_rt ::ffi::ngx_command_t;
+                        <<#ident as #ngx_mod ::mail::Module> :: MainConf as #ngx_rt ::core::UnsafeConf>::COMMANDS.len() +
+                        <<#ident as #ngx_mod ::mail::Module> :: SrvConf as #ngx_rt ::core::UnsafeConf>::COMMANDS.len() +
+                    1] = unsafe {
+                        #ngx_mod ::const_concat!(
+                            <<#ident as #ngx_mod ::mail::Module> :: MainConf as #ngx_rt ::core::UnsafeConf>::COMMANDS,
+                            <<#ident as #ngx_mod ::mail::Module> :: SrvConf as #ngx_rt ::core::UnsafeConf>::COMMANDS,
+                            [ #ngx_rt ::ngx_command!() ]
+                        )
+                    };
+                }),
+            ),
             _ => (None, None),
         };
 
+    let registered = match &module_ty {
+        #[cfg(feature = "http")]
+        Type::Http(_) => Some(quote! {
+            const _: () = {
+                #[#ngx_mod ::linkme::distributed_slice(#ngx_mod ::http::MODULES)]
+                #[linkme(crate = #ngx_mod ::linkme)]
+                static MODULE: &str = ::std::env!("CARGO_CRATE_NAME");
+            };
+        }),
+        _ => None,
+    };
+
     let ngx_modules: ItemStatic = parse_quote! {
         #[no_mangle]
         pub static mut ngx_modules: [*const #ngx_rt ::ffi::ngx_module_t; 2] = [
@@ -327,6 +481,8 @@ pub fn expand(input: syn::DeriveInput) -> TokenStream {
 
         #ngx_module_ctx
         #ngx_module_cmds
+        #ngx_module_doc
+        #registered
 
         #ngx_modules
         #ngx_module_names
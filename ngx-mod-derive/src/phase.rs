@@ -0,0 +1,95 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use structmeta::{NameValue, StructMeta};
+use syn::{spanned::Spanned, FnArg, ItemFn, Pat, Path, Signature};
+
+use crate::{
+    util::{find_ngx_mod, find_ngx_rt},
+    var::registered_module,
+};
+
+#[derive(Clone, Debug, StructMeta)]
+pub struct Args {
+    #[struct_meta(unnamed)]
+    phase: Path,
+    module: Option<NameValue<Path>>,
+}
+
+pub fn expand(args: Args, f: ItemFn) -> TokenStream {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = f;
+    let Signature {
+        ident,
+        generics,
+        inputs,
+        output,
+        ..
+    } = sig;
+
+    if !generics.params.is_empty() {
+        abort!(generics.span(), "phase handler should not be generic");
+    }
+
+    let req = match inputs.first() {
+        Some(FnArg::Typed(pt)) if inputs.len() == 1 => match pt.pat.as_ref() {
+            Pat::Ident(pi) => &pi.ident,
+            _ => abort!(pt.span(), "only support ident pattern in function argument"),
+        },
+        _ => abort!(
+            inputs.span(),
+            "phase handler should take a single `&RequestRef` argument"
+        ),
+    };
+
+    let ngx_rt = find_ngx_rt();
+    let ngx_mod = find_ngx_mod();
+    let module = registered_module(&ngx_mod, args.module.as_ref().map(|arg| &arg.value));
+    let phase = &args.phase;
+
+    if let Some(name) = phase.segments.last().map(|s| s.ident.to_string()) {
+        if ["FindConfig", "PostRewrite", "PostAccess"].contains(&name.as_str()) {
+            abort!(
+                phase.span(),
+                "the `{}` phase does not accept handlers",
+                name
+            );
+        }
+    }
+
+    let phase = if phase.get_ident().is_some() {
+        quote! { #ngx_rt ::http::core::Phases:: #phase }
+    } else {
+        quote! { #phase }
+    };
+
+    quote! {
+        #( #attrs )*
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        impl #ngx_mod ::http::PhaseHandler for #ident {
+            const PHASE: #ngx_rt ::http::core::Phases = #phase;
+
+            fn handle(#req: &#ngx_rt ::http::RequestRef) -> #ngx_rt ::http::core::PhaseResult {
+                fn handler( #inputs ) #output #block
+
+                handler(#req).into()
+            }
+        }
+
+        const _: () = {
+            #[#ngx_mod ::linkme::distributed_slice(#ngx_mod ::http::PHASE_HANDLERS)]
+            #[linkme(crate = #ngx_mod ::linkme)]
+            static HANDLER: #ngx_mod ::http::Registered<#ngx_mod ::http::RawPhaseHandler> = #ngx_mod ::http::Registered {
+                krate: ::std::env!("CARGO_CRATE_NAME"),
+                module: #module,
+                item: <#ident as #ngx_mod ::http::PhaseHandler>::HANDLER,
+            };
+        };
+    }
+}
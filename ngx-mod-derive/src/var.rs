@@ -0,0 +1,222 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use structmeta::{Flag, NameValue, StructMeta};
+use syn::{
+    spanned::Spanned, Expr, ExprBinary, ExprParen, ExprPath, FnArg, ItemFn, LitStr, Pat, Path,
+    Signature,
+};
+
+use crate::util::{find_ngx_mod, find_ngx_rt};
+
+#[derive(Clone, Debug, StructMeta)]
+pub struct Args {
+    name: Option<NameValue<LitStr>>,
+    flags: Option<NameValue<Expr>>,
+    set: Option<NameValue<Path>>,
+    prefix: Flag,
+    module: Option<NameValue<Path>>,
+}
+
+pub fn expand(args: Args, f: ItemFn) -> TokenStream {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = f;
+    let Signature {
+        ident,
+        generics,
+        inputs,
+        output,
+        ..
+    } = sig;
+
+    if !generics.params.is_empty() {
+        abort!(generics.span(), "variable handler should not be generic");
+    }
+
+    let params = inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(pt) => match pt.pat.as_ref() {
+                Pat::Ident(pi) => &pi.ident,
+                _ => abort!(pt.span(), "only support ident pattern in function argument"),
+            },
+            FnArg::Receiver(_) => abort!(arg.span(), "variable handler should not take `self`"),
+        })
+        .collect::<Vec<_>>();
+
+    let prefix = args.prefix.value();
+
+    match (prefix, params.len()) {
+        (false, 1) | (true, 2) => {}
+        (false, _) => abort!(
+            inputs.span(),
+            "variable handler should take a single `&RequestRef` argument"
+        ),
+        (true, _) => abort!(
+            inputs.span(),
+            "prefix variable handler should take `&RequestRef` and `&str` arguments"
+        ),
+    }
+
+    let ngx_rt = find_ngx_rt();
+    let ngx_mod = find_ngx_mod();
+    let name = args.name.as_ref().map_or_else(
+        || LitStr::new(&ident.to_string(), ident.span()),
+        |arg| arg.value.clone(),
+    );
+    let flags = args.flags.as_ref().map(|arg| flags(&ngx_rt, &arg.value));
+    let flags = match (flags, prefix) {
+        (Some(flags), true) => quote! { (#flags) | #ngx_rt ::ffi::NGX_HTTP_VAR_PREFIX },
+        (Some(flags), false) => flags,
+        (None, true) => quote! { #ngx_rt ::ffi::NGX_HTTP_VAR_PREFIX },
+        (None, false) => quote! { 0 },
+    };
+    let (flags, set_handler) = match args.set.as_ref().map(|arg| &arg.value) {
+        Some(set) if prefix => abort!(set.span(), "prefix variable can't be set"),
+        Some(set) => (
+            quote! { (#flags) | #ngx_rt ::ffi::NGX_HTTP_VAR_CHANGEABLE },
+            quote! {
+                unsafe extern "C" fn set_handler(
+                    r: *mut #ngx_rt ::ffi::ngx_http_request_t,
+                    v: *mut #ngx_rt ::ffi::ngx_http_variable_value_t,
+                    _data: usize,
+                ) {
+                    let req = <#ngx_rt ::http::RequestRef as #ngx_rt ::foreign_types::ForeignTypeRef>::from_ptr(r);
+                    let val = <#ngx_rt ::http::ValueRef as #ngx_rt ::foreign_types::ForeignTypeRef>::from_ptr(v);
+
+                    #set(req, val.as_bytes())
+                }
+            },
+        ),
+        None => (flags, quote! {}),
+    };
+    let set = if args.set.is_some() {
+        quote! { Some(Self::set_handler) }
+    } else {
+        quote! { None }
+    };
+
+    let module = registered_module(&ngx_mod, args.module.as_ref().map(|arg| &arg.value));
+
+    let req = params[0];
+    let call = if prefix {
+        quote! {
+            let suffix = match (data as *const #ngx_rt ::ffi::ngx_str_t)
+                .as_ref()
+                .map(|s| ::std::slice::from_raw_parts(s.data, s.len))
+                .and_then(|s| s.get(#name.len()..))
+                .and_then(|s| ::std::str::from_utf8(s).ok())
+            {
+                Some(s) => s,
+                None => {
+                    val.set_not_found(true);
+
+                    return #ngx_rt ::ffi::NGX_OK as #ngx_rt ::ffi::ngx_int_t;
+                }
+            };
+
+            let value = handler(#req, suffix);
+        }
+    } else {
+        quote! {
+            let _ = data;
+            let value = handler(#req);
+        }
+    };
+
+    quote! {
+        #( #attrs )*
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        impl #ident {
+            unsafe extern "C" fn get_handler(
+                r: *mut #ngx_rt ::ffi::ngx_http_request_t,
+                v: *mut #ngx_rt ::ffi::ngx_http_variable_value_t,
+                data: usize,
+            ) -> #ngx_rt ::ffi::ngx_int_t {
+                fn handler( #inputs ) #output #block
+
+                let #req = <#ngx_rt ::http::RequestRef as #ngx_rt ::foreign_types::ForeignTypeRef>::from_ptr(r);
+                let val = <#ngx_rt ::http::ValueRef as #ngx_rt ::foreign_types::ForeignTypeRef>::from_ptr_mut(v);
+
+                #call
+
+                <Self as #ngx_mod ::http::Variable>::bind(#req, val, value.as_ref())
+                    .map_or_else(
+                        #ngx_rt ::ffi::ngx_int_t::from,
+                        |_| #ngx_rt ::ffi::NGX_OK as #ngx_rt ::ffi::ngx_int_t,
+                    )
+            }
+
+            #set_handler
+        }
+
+        impl #ngx_mod ::http::Variable for #ident {
+            const VARIABLE: #ngx_rt ::http::RawVar = #ngx_rt ::ffi::ngx_http_variable_t {
+                name: #ngx_rt ::ngx_str!(#name),
+                set_handler: #set,
+                get_handler: Some(Self::get_handler),
+                data: 0,
+                flags: (#flags) as usize,
+                index: 0,
+            };
+        }
+
+        const _: () = {
+            #[#ngx_mod ::linkme::distributed_slice(#ngx_mod ::http::VARIABLES)]
+            #[linkme(crate = #ngx_mod ::linkme)]
+            static VARIABLE: #ngx_mod ::http::Registered<#ngx_rt ::http::RawVar> = #ngx_mod ::http::Registered {
+                krate: ::std::env!("CARGO_CRATE_NAME"),
+                module: #module,
+                item: <#ident as #ngx_mod ::http::Variable>::VARIABLE,
+            };
+        };
+    }
+}
+
+/// Returns the module of the registered item, e.g. `module = Foo`,
+/// or `None` for the only HTTP module of the crate.
+pub fn registered_module(ngx_mod: &Path, module: Option<&Path>) -> TokenStream {
+    match module {
+        Some(module) => quote! { Some(<#module as #ngx_mod ::ModuleMetadata>::module) },
+        None => quote! { None },
+    }
+}
+
+/// Maps the flag names, e.g. `NOCACHEABLE` or `NO_CACHEABLE`, to `NGX_HTTP_VAR_*` constants.
+fn flags(ngx_rt: &Path, expr: &Expr) -> TokenStream {
+    match expr {
+        Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) => {
+            let left = flags(ngx_rt, left);
+            let right = flags(ngx_rt, right);
+
+            quote! { #left #op #right }
+        }
+        Expr::Paren(ExprParen { expr, .. }) => {
+            let expr = flags(ngx_rt, expr);
+
+            quote! { ( #expr ) }
+        }
+        Expr::Path(ExprPath { path, .. }) if path.get_ident().is_some() => {
+            let ident = path.get_ident().unwrap();
+            let name = ident.to_string().replace('_', "").to_uppercase();
+
+            match name.as_str() {
+                "CHANGEABLE" | "NOCACHEABLE" | "INDEXED" | "NOHASH" | "WEAK" | "PREFIX" => {
+                    let flag = format_ident!("NGX_HTTP_VAR_{}", name, span = ident.span());
+
+                    quote! { #ngx_rt ::ffi:: #flag }
+                }
+                _ => abort!(ident.span(), "unknown variable flag `{}`", ident),
+            }
+        }
+        _ => quote! { #expr },
+    }
+}
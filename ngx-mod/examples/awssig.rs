@@ -2,14 +2,14 @@
 #![cfg(not(feature = "static-link"))]
 
 use http::{HeaderMap, StatusCode};
-use merge::Merge as AutoMerge;
 
 use ngx_mod::{
     http::Module as HttpModule,
+    merge::MergeError,
     rt::{
-        core::{CmdRef, Code, ConfRef},
+        core::{Code, ConfRef},
         http::core::{self, Phases},
-        http_debug, native_setter, notice,
+        http_debug, notice,
     },
     Conf, Merge, Module,
 };
@@ -22,7 +22,7 @@ struct AwsSig;
 impl Module for AwsSig {}
 
 impl HttpModule for AwsSig {
-    type Error = ();
+    type Error = MergeError;
     type MainConf = ();
     type SrvConf = ();
     type LocConf = Config;
@@ -43,108 +43,28 @@ impl HttpModule for AwsSig {
     }
 }
 
-#[derive(Clone, Debug, Default, AutoMerge, Conf)]
+#[derive(Clone, Debug, Default, Conf, Merge)]
 #[conf(http::server | http::location)]
 struct Config {
-    #[directive(name = "awssigv4", args(1), set = ngx_http_awssigv4_commands_set_enable)]
-    #[merge(strategy = merge::bool::overwrite_false)]
-    enable: bool,
-    #[directive(name = "awssigv4_access_key", args(1), set = ngx_http_awssigv4_commands_set_access_key)]
+    #[directive(name = "awssigv4", args(1))]
+    enable: Option<bool>,
+    #[directive(name = "awssigv4_access_key", args(1))]
     access_key: Option<String>,
-    #[directive(name = "awssigv4_secret_key", args(1), set = ngx_http_awssigv4_commands_set_secret_key)]
+    #[directive(name = "awssigv4_secret_key", args(1))]
     secret_key: Option<String>,
-    #[directive(name = "awssigv4_s3_bucket", args(1), set = ngx_http_awssigv4_commands_set_s3_bucket)]
+    #[directive(name = "awssigv4_s3_bucket", args(1))]
     s3_bucket: Option<String>,
-    #[directive(name = "awssigv4_s3_endpoint", args(1), set = ngx_http_awssigv4_commands_set_s3_endpoint)]
+    #[directive(name = "awssigv4_s3_endpoint", args(1))]
     s3_endpoint: Option<String>,
 }
 
-impl Merge for Config {
-    type Error = ();
-
-    fn merge(&mut self, prev: &Config) -> Result<(), ()> {
-        merge::Merge::merge(self, prev.clone());
-
-        Ok(())
-    }
-}
-
-#[native_setter(name = ngx_http_awssigv4_commands_set_enable, log = cf)]
-fn set_enable(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Config) -> anyhow::Result<()> {
-    conf.enable = if let Some(s) = cf.args().get(1) {
-        s.to_str()?.eq_ignore_ascii_case("on")
-    } else {
-        false
-    };
-
-    notice!(cf, "AwsSig set enable: {}", conf.enable);
-
-    Ok(())
-}
-
-#[native_setter(name = ngx_http_awssigv4_commands_set_access_key, log = cf)]
-fn set_access_key(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Config) -> anyhow::Result<()> {
-    conf.access_key = cf
-        .args()
-        .get(1)
-        .map(|s| s.to_str())
-        .transpose()?
-        .map(|s| s.to_string());
-
-    notice!(cf, "AwsSig set access key: {:?}", conf.access_key);
-
-    Ok(())
-}
-
-#[native_setter(name = ngx_http_awssigv4_commands_set_secret_key, log = cf)]
-fn set_secret_key(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Config) -> anyhow::Result<()> {
-    conf.secret_key = cf
-        .args()
-        .get(1)
-        .map(|s| s.to_str())
-        .transpose()?
-        .map(|s| s.to_string());
-
-    notice!(cf, "AwsSig set secret key: {:?}", conf.secret_key);
-
-    Ok(())
-}
-
-#[native_setter(name = ngx_http_awssigv4_commands_set_s3_bucket, log = cf)]
-fn set_s3_bucket(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Config) -> anyhow::Result<()> {
-    conf.s3_bucket = cf
-        .args()
-        .get(1)
-        .map(|s| s.to_str())
-        .transpose()?
-        .map(|s| s.to_string());
-
-    notice!(cf, "AwsSig set S3 bucket: {:?}", conf.s3_bucket);
-
-    Ok(())
-}
-
-#[native_setter(name = ngx_http_awssigv4_commands_set_s3_endpoint, log = cf)]
-fn set_s3_endpoint(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Config) -> anyhow::Result<()> {
-    conf.s3_endpoint = cf
-        .args()
-        .get(1)
-        .map(|s| s.to_str())
-        .transpose()?
-        .map(|s| s.to_string());
-
-    notice!(cf, "AwsSig set S3 endpoint: {:?}", conf.s3_endpoint);
-
-    Ok(())
-}
-
 #[native_handler(name = awssigv4_header_handler, embedded)]
 fn header_handler(req: &mut RequestRef) -> Result<Code, Code> {
     let conf = AwsSig::loc_conf(req).ok_or_else(|| Code::ERROR)?;
 
     http_debug!(req, "AwsSig module: {:?}", conf);
 
-    if !conf.enable {
+    if conf.enable != Some(true) {
         return Err(Code::DECLINED);
     }
 
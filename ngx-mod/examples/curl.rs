@@ -2,18 +2,19 @@
 #![cfg(not(feature = "static-link"))]
 
 use http::StatusCode;
-use merge::Merge as AutoMerge;
 
 use ngx_mod::{
     http::Module as HttpModule,
+    merge::MergeError,
+    phase_handler,
     rt::{
-        core::{CmdRef, Code, ConfRef},
-        http::core::{self, Phases},
-        http_debug, native_setter, notice,
+        core::{Code, ConfRef},
+        http::core::PhaseResult,
+        http_debug, notice,
     },
     Conf, Merge, Module,
 };
-use ngx_rt::{http::RequestRef, native_handler};
+use ngx_rt::http::RequestRef;
 
 #[derive(Module)]
 #[module(name = ngx_http_curl, type = http)]
@@ -22,7 +23,7 @@ struct Curl;
 impl Module for Curl {}
 
 impl HttpModule for Curl {
-    type Error = ();
+    type Error = MergeError;
     type MainConf = ();
     type SrvConf = ();
     type LocConf = LocConfig;
@@ -30,63 +31,32 @@ impl HttpModule for Curl {
     fn postconfiguration(cf: &ConfRef) -> Result<(), Code> {
         notice!(cf, "CURL init module");
 
-        let cmcf = cf
-            .as_http_context()
-            .and_then(core::main_conf_mut)
-            .ok_or(Code::ERROR)?;
-
-        cmcf.phases_mut(Phases::Access)
-            .handlers_mut()
-            .push(Some(ngx_http_curl_access_handler));
-
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Default, AutoMerge, Conf)]
+#[derive(Clone, Debug, Default, Conf, Merge)]
 #[conf(http::location)]
 struct LocConfig {
-    #[directive(name = "curl", args(1), set = ngx_http_curl_commands_set_enable)]
-    #[merge(strategy = merge::bool::overwrite_false)]
-    enable: bool,
+    #[directive(name = "curl", args(1))]
+    enable: Option<bool>,
 }
 
-impl Merge for LocConfig {
-    type Error = ();
-
-    fn merge(&mut self, prev: &LocConfig) -> Result<(), ()> {
-        merge::Merge::merge(self, prev.clone());
-
-        Ok(())
-    }
-}
-
-#[native_setter(name = ngx_http_curl_commands_set_enable, log = cf)]
-fn set_enable(cf: &ConfRef, _cmd: &CmdRef, conf: &mut LocConfig) -> anyhow::Result<()> {
-    notice!(cf, "CURL set enable");
-
-    conf.enable = if let Some(s) = cf.args().get(1) {
-        s.to_str()?.eq_ignore_ascii_case("on")
-    } else {
-        false
+#[phase_handler(Access)]
+fn curl_access(req: &RequestRef) -> PhaseResult {
+    let Some(lc) = Curl::loc_conf(req) else {
+        return PhaseResult::Status(StatusCode::INTERNAL_SERVER_ERROR);
     };
 
-    Ok(())
-}
-
-#[native_handler(name = ngx_http_curl_access_handler)]
-fn curl_access(req: &RequestRef) -> Result<StatusCode, Code> {
-    let lc = Curl::loc_conf(req).ok_or(Code::ERROR)?;
-
-    http_debug!(req, "CURL enabled: {}", lc.enable);
+    http_debug!(req, "CURL enabled: {:?}", lc.enable);
 
-    if lc.enable
+    if lc.enable == Some(true)
         && req
             .user_agent()
             .map_or(false, |h| h.value().as_bytes().starts_with(b"curl"))
     {
-        Ok(StatusCode::FORBIDDEN)
+        PhaseResult::Status(StatusCode::FORBIDDEN)
     } else {
-        Err(Code::DECLINED)
+        PhaseResult::Declined
     }
 }
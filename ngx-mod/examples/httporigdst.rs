@@ -10,11 +10,11 @@ use socket2::SockAddr;
 use ngx_mod::{
     http::{self, Module as _},
     rt::{
-        core::{errno, Code, ConfRef, PoolRef, Str},
-        http::{ModuleContext, RequestRef, ValueRef},
-        http_debug, native_handler, ngx_var, notice,
+        core::{errno, Code, ConfRef},
+        http::RequestRef,
+        http_debug, notice,
     },
-    Module, ModuleMetadata,
+    variable, Module,
 };
 
 #[derive(Module)]
@@ -32,11 +32,7 @@ impl http::Module for OrigDst {
     fn preconfiguration(cf: &ConfRef) -> Result<(), Code> {
         notice!(cf, "httporigdst: init module");
 
-        cf.add_variables([
-            ngx_var!("server_orig_addr", get = ngx_http_orig_dst_addr_variable),
-            ngx_var!("server_orig_port", get = ngx_http_orig_dst_port_variable),
-        ])
-        .map_err(|_| Code::ERROR)
+        Ok(())
     }
 }
 
@@ -94,79 +90,32 @@ fn get_origdst(req: &RequestRef) -> Result<SocketAddrV4, Code> {
     }
 }
 
-#[native_handler(name = ngx_http_orig_dst_addr_variable)]
-fn server_orig_addr(req: &RequestRef, val: &mut ValueRef, _data: usize) -> Result<(), Code> {
-    if let Some(ctx) = OrigDst::module_ctx::<_, OrigDstCtx>(req) {
-        http_debug!(req, "httporigdst: found context and binding variable");
-
-        ctx.bind_addr(val);
-    } else {
-        http_debug!(req, "httporigdst: context not found, getting address");
-
-        let addr = get_origdst(req)?;
-
-        if let Some(ctx) = req.pool().allocate_default::<OrigDstCtx>() {
-            http_debug!(req, "httporigdst: saving addr: {}", addr);
-
-            ctx.save(req.pool(), addr)?.bind_addr(val);
-
-            req.set_module_ctx(OrigDst::module(), ctx)
-        }
-    }
+#[variable(name = "server_orig_addr")]
+fn server_orig_addr(req: &RequestRef) -> Option<String> {
+    orig_dst(req).map(|addr| addr.ip().to_string())
+}
 
-    Ok(())
+#[variable(name = "server_orig_port")]
+fn server_orig_port(req: &RequestRef) -> Option<String> {
+    orig_dst(req).map(|addr| addr.port().to_string())
 }
 
-#[native_handler(name = ngx_http_orig_dst_port_variable)]
-fn server_orig_port(req: &RequestRef, val: &mut ValueRef, _data: usize) -> Result<(), Code> {
-    if let Some(ctx) = OrigDst::module_ctx::<_, OrigDstCtx>(req) {
+fn orig_dst(req: &RequestRef) -> Option<SocketAddrV4> {
+    if let Some(addr) = OrigDst::module_ctx::<_, SocketAddrV4>(req) {
         http_debug!(req, "httporigdst: found context and binding variable");
 
-        ctx.bind_port(val);
-    } else {
-        http_debug!(req, "httporigdst: context not found, getting address");
-
-        let addr = get_origdst(req)?;
-
-        if let Some(ctx) = req.pool().allocate_default::<OrigDstCtx>() {
-            http_debug!(req, "httporigdst: saving addr: {}", addr);
-
-            ctx.save(req.pool(), addr)?.bind_port(val);
-
-            req.set_module_ctx(OrigDst::module(), ctx)
-        }
+        return Some(*addr);
     }
 
-    Ok(())
-}
+    http_debug!(req, "httporigdst: context not found, getting address");
 
-#[derive(Clone, Debug, Default)]
-struct OrigDstCtx {
-    orig_dst_addr: Str,
-    orig_dst_port: Str,
-}
+    let addr = get_origdst(req).ok()?;
 
-impl OrigDstCtx {
-    pub fn save(&mut self, p: &PoolRef, addr: SocketAddrV4) -> Result<&Self, Code> {
-        self.orig_dst_addr = p.strdup(addr.ip().to_string()).ok_or(Code::ERROR)?;
-        self.orig_dst_port = p.strdup(addr.port().to_string()).ok_or(Code::ERROR)?;
+    if let Some(ctx) = req.pool().allocate(addr) {
+        http_debug!(req, "httporigdst: saving addr: {}", addr);
 
-        Ok(self)
+        OrigDst::set_module_ctx(req, ctx);
     }
 
-    pub fn bind_addr(&self, v: &mut ValueRef) {
-        if self.orig_dst_addr.is_empty() {
-            v.set_not_found(true);
-        } else {
-            v.set_value(self.orig_dst_addr);
-        }
-    }
-
-    pub fn bind_port(&self, v: &mut ValueRef) {
-        if self.orig_dst_port.is_empty() {
-            v.set_not_found(true);
-        } else {
-            v.set_value(self.orig_dst_port);
-        }
-    }
+    Some(addr)
 }
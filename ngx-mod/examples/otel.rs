@@ -8,7 +8,7 @@ use std::ptr;
 use std::time::Duration;
 use std::time::SystemTime;
 
-use anyhow::{anyhow, bail, Context as _};
+use anyhow::anyhow;
 use foreign_types::ForeignTypeRef;
 use opentelemetry::{
     global,
@@ -99,20 +99,17 @@ impl<'a> http::Module for Otel<'a> {
             .and_then(core::main_conf_mut)
             .ok_or(Code::ERROR)?;
 
-        cmcf.push_handler(Phases::Rewrite, otel_request_start);
-        cmcf.push_handler(Phases::Log, otel_request_end);
+        cmcf.push_handler(Phases::Rewrite, otel_request_start)?;
+        cmcf.push_handler(Phases::Log, otel_request_end)?;
 
         Ok(())
     }
 
     fn init_main_conf(cf: &ConfRef, conf: &mut Self::MainConf) -> Result<(), Self::Error> {
-        info!(cf, "otel: init main conf");
-
-        conf.exporter.interval.or_insert(5000.into());
-        conf.exporter.batch_size.or_insert(512);
-        conf.exporter.batch_count.or_insert(4);
-        conf.service_name
-            .or_insert_with(|| Str::from("unknown_service:nginx"));
+        info!(
+            cf,
+            "otel: init main conf, service name: {}", conf.service_name
+        );
 
         Ok(())
     }
@@ -337,9 +334,9 @@ fn parent_sampled_var(req: &RequestRef, val: &mut ValueRef, _data: usize) -> Res
 #[derive(Clone, Conf)]
 #[conf(http::main, default = unset)]
 struct MainConf {
-    #[directive(name = "otel_exporter", args(0), block, set = set_exporter)]
+    #[directive(name = "otel_exporter", args(0), block)]
     exporter: Exporter,
-    #[directive(name = "otel_service_name", args(1))]
+    #[directive(name = "otel_service_name", args(1), default = "unknown_service:nginx")]
     service_name: Str,
 }
 
@@ -369,29 +366,17 @@ impl MainConf {
     }
 }
 
-#[native_setter(log = cf)]
-fn set_exporter(cf: &ConfRef, _cmd: &CmdRef, conf: &mut MainConf) -> anyhow::Result<()> {
-    if !conf.exporter.endpoint.is_empty() {
-        bail!("exporter is duplicate");
-    }
-
-    cf.parse_block(&mut conf.exporter)
-        .context("parse exporter")?;
-
-    Ok(())
-}
-
 #[repr(C)]
 #[derive(Clone, Conf)]
 #[conf(default = unset)]
 struct Exporter {
     #[directive(args(1), set = str)]
     endpoint: Str,
-    #[directive(args(1), set = msec)]
+    #[directive(args(1), set = msec, default = "5s")]
     interval: MSec,
-    #[directive(args(1), set = size)]
+    #[directive(args(1), set = size, default = 512)]
     batch_size: usize,
-    #[directive(args(1), set = size)]
+    #[directive(args(1), set = size, default = 4)]
     batch_count: usize,
 }
 
@@ -401,7 +386,7 @@ struct Exporter {
 struct LocConf<'a> {
     #[directive(name = "otel_trace", args(1), set = complex_value)]
     trace: Option<&'a ComplexValueRef>,
-    #[directive(name = "otel_trace_context", args(1), set = enum_values, values = propagation::TYPES)]
+    #[directive(name = "otel_trace_context", args(1), set = enum_values, values = propagation::TYPES, default = "ignore")]
     trace_ctx: usize,
     #[directive(name = "otel_span_name", args(1), set = complex_value)]
     span_name: Option<&'a ComplexValueRef>,
@@ -464,7 +449,7 @@ mod propagation {
     }
 }
 
-#[native_setter(log = cf)]
+#[native_setter]
 fn add_span_attr(cf: &ConfRef, _cmd: &CmdRef, conf: &mut LocConf) -> anyhow::Result<()> {
     let span_attrs = conf.span_attrs_mut();
     if span_attrs.is_null() {
@@ -0,0 +1,171 @@
+#![crate_type = "dylib"]
+#![cfg(not(feature = "static-link"))]
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use foreign_types::ForeignTypeRef;
+
+use ngx_mod::{
+    rt::{
+        core::{CmdRef, Code, ConfRef, ConnRef},
+        event::EventRef,
+        native_handler, native_setter, notice,
+        stream::{self as stream_rt, SessionRef, Status},
+        stream_debug, stream_error, AsRawRef,
+    },
+    stream::{self, Module as _},
+    Conf, Merge, Module,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Module)]
+#[module(name = ngx_stream_echo, type = stream)]
+struct Echo;
+
+impl Module for Echo {}
+
+impl stream::Module for Echo {
+    type Error = ();
+    type MainConf = ();
+    type SrvConf = SrvConfig;
+}
+
+#[derive(Clone, Debug, Default, Conf)]
+#[conf(stream::server)]
+struct SrvConfig {
+    #[directive(args(0), set = ngx_stream_echo)]
+    echo: bool,
+}
+
+impl Merge for SrvConfig {
+    type Error = ();
+
+    fn merge(&mut self, prev: &SrvConfig) -> Result<(), ()> {
+        self.echo |= prev.echo;
+
+        Ok(())
+    }
+}
+
+#[native_setter(name = ngx_stream_echo)]
+fn set_echo(cf: &ConfRef, _cmd: &CmdRef, conf: &mut SrvConfig) -> anyhow::Result<()> {
+    notice!(cf, "ECHO init server");
+
+    conf.echo = true;
+
+    cf.as_stream_context()
+        .and_then(stream_rt::core::srv_conf_mut)
+        .ok_or_else(|| anyhow!("`srv_conf` not found"))?
+        .set_handler(ngx_stream_echo_handler);
+
+    Ok(())
+}
+
+/// The data received from the client which is not sent back yet.
+struct Ctx {
+    buf: [u8; 4096],
+    pos: usize,
+    last: usize,
+}
+
+impl Default for Ctx {
+    fn default() -> Self {
+        Ctx {
+            buf: [0; 4096],
+            pos: 0,
+            last: 0,
+        }
+    }
+}
+
+#[native_handler(name = ngx_stream_echo_handler)]
+fn echo_handler(s: &SessionRef) {
+    let c = s.connection();
+
+    if s.is_udp() {
+        let datagram = unsafe {
+            let b = c.buffer().as_raw();
+
+            std::slice::from_raw_parts(b.pos, b.last.offset_from(b.pos) as usize)
+        };
+
+        stream_debug!(s, "ECHO datagram, {} bytes", datagram.len());
+
+        let status = match c.send(datagram) {
+            Ok(_) => Status::Ok,
+            Err(_) => Status::InternalServerError,
+        };
+
+        return s.finalize(status);
+    }
+
+    let Some(ctx) = c.pool().allocate_default::<Ctx>() else {
+        return s.finalize(Status::InternalServerError);
+    };
+
+    Echo::set_module_ctx(s, ctx);
+
+    unsafe {
+        let rev = EventRef::from_ptr_mut(c.as_raw().read);
+        let wev = EventRef::from_ptr_mut(c.as_raw().write);
+
+        rev.set_handler(ngx_stream_echo_event_handler);
+        wev.set_handler(ngx_stream_echo_event_handler);
+
+        ngx_stream_echo_event_handler(rev.as_ptr());
+    }
+}
+
+#[native_handler(name = ngx_stream_echo_event_handler)]
+fn echo_event_handler(ev: &mut EventRef) {
+    let c = unsafe { ConnRef::from_ptr(ev.as_raw().data.cast()) };
+    let s = unsafe { SessionRef::from_ptr(c.as_raw().data.cast()) };
+
+    if ev.timedout() {
+        stream_error!(s, "ECHO client timed out");
+
+        return s.finalize(Status::Ok);
+    }
+
+    let Some(ctx) = Echo::module_ctx_mut::<_, Ctx>(s) else {
+        return s.finalize(Status::InternalServerError);
+    };
+
+    loop {
+        if ctx.pos < ctx.last {
+            // send the pending data back before reading more
+            match c.send(&ctx.buf[ctx.pos..ctx.last]) {
+                Ok(n) => ctx.pos += n,
+                Err(Code::AGAIN) => break,
+                Err(_) => return s.finalize(Status::InternalServerError),
+            }
+
+            continue;
+        }
+
+        match c.recv(&mut ctx.buf) {
+            Ok(0) => return s.finalize(Status::Ok),
+            Ok(n) => {
+                stream_debug!(s, "ECHO received {} bytes", n);
+
+                ctx.pos = 0;
+                ctx.last = n;
+            }
+            Err(Code::AGAIN) => break,
+            Err(_) => return s.finalize(Status::BadRequest),
+        }
+    }
+
+    unsafe {
+        let rev = EventRef::from_ptr_mut(c.as_raw().read);
+        let wev = EventRef::from_ptr_mut(c.as_raw().write);
+
+        rev.add_timer(TIMEOUT);
+
+        if rev.handle_read().is_err() || wev.handle_write(0).is_err() {
+            s.finalize(Status::InternalServerError);
+        }
+    }
+}
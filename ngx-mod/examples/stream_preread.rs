@@ -0,0 +1,182 @@
+#![crate_type = "dylib"]
+#![cfg(not(feature = "static-link"))]
+
+use ngx_mod::{
+    rt::{
+        core::{Code, ConfRef, ValueRef},
+        native_handler, ngx_stream_var,
+        stream::{Preread, SessionRef, Status},
+        stream_debug,
+    },
+    stream, Module,
+};
+
+#[derive(Module)]
+#[module(name = ngx_stream_preread_proto, type = stream)]
+struct PrereadProto;
+
+impl Module for PrereadProto {}
+
+impl stream::Module for PrereadProto {
+    type Error = ();
+    type MainConf = ();
+    type SrvConf = ();
+
+    fn preconfiguration(cf: &ConfRef) -> Result<(), Code> {
+        cf.add_stream_variables([
+            ngx_stream_var!(
+                "preread_protocol",
+                get = ngx_stream_preread_protocol_variable
+            ),
+            ngx_stream_var!(
+                "preread_client_id",
+                get = ngx_stream_preread_client_id_variable
+            ),
+        ])
+        .map_err(|_| Code::ERROR)
+    }
+
+    fn postconfiguration(cf: &ConfRef) -> Result<(), Code> {
+        Self::add_preread_handler(cf)
+    }
+
+    fn preread(s: &SessionRef) -> Preread {
+        let data = s.preread();
+
+        let proto = match Protocol::sniff(data) {
+            Some(proto) => proto,
+            None if s.preread_full() => Protocol::Unknown,
+            None => return Preread::Again,
+        };
+
+        stream_debug!(s, "PREREAD {:?} after {} bytes", proto, data.len());
+
+        match s.connection().pool().allocate(proto) {
+            Some(ctx) => {
+                Self::set_module_ctx(s, ctx);
+
+                Preread::Ok
+            }
+            None => Preread::Finalize(Status::InternalServerError),
+        }
+    }
+}
+
+#[native_handler(name = ngx_stream_preread_protocol_variable)]
+fn preread_protocol(s: &SessionRef, val: &mut ValueRef, _data: usize) -> Result<(), Code> {
+    let proto = PrereadProto::module_ctx::<_, Protocol>(s).ok_or(Code::DECLINED)?;
+
+    val.set_value(proto.name());
+
+    Ok(())
+}
+
+#[native_handler(name = ngx_stream_preread_client_id_variable)]
+fn preread_client_id(s: &SessionRef, val: &mut ValueRef, _data: usize) -> Result<(), Code> {
+    let id = PrereadProto::module_ctx::<_, Protocol>(s)
+        .and_then(Protocol::client_id)
+        .ok_or(Code::DECLINED)?;
+
+    val.set_value(id);
+
+    Ok(())
+}
+
+/// The protocol recognized from the first bytes sent by the client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Tls,
+    Mqtt { client_id: String },
+    Postgres { user: Option<String> },
+    Unknown,
+}
+
+impl Protocol {
+    const PG_PROTOCOL_V3: u32 = 196608;
+    const PG_SSL_REQUEST: u32 = 80877103;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::Tls => "tls",
+            Protocol::Mqtt { .. } => "mqtt",
+            Protocol::Postgres { .. } => "postgres",
+            Protocol::Unknown => "unknown",
+        }
+    }
+
+    pub fn client_id(&self) -> Option<&str> {
+        match self {
+            Protocol::Mqtt { client_id } => Some(client_id),
+            Protocol::Postgres { user } => user.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns `None` if more data is needed to recognize the protocol.
+    pub fn sniff(data: &[u8]) -> Option<Protocol> {
+        match data.first()? {
+            0x16 => Some(Protocol::Tls),
+            0x10 => Self::mqtt(data),
+            0x00 => Self::postgres(data),
+            _ => Some(Protocol::Unknown),
+        }
+    }
+
+    fn mqtt(data: &[u8]) -> Option<Protocol> {
+        let mut len = 0usize;
+        let mut off = 1;
+
+        // variable length encoding of the remaining length
+        loop {
+            let b = *data.get(off)?;
+
+            len |= ((b & 0x7F) as usize) << (7 * (off - 1));
+            off += 1;
+
+            if b & 0x80 == 0 {
+                break;
+            }
+            if off > 4 {
+                return Some(Protocol::Unknown);
+            }
+        }
+
+        let packet = data.get(off..off + len)?;
+
+        let name_len = u16::from_be_bytes(packet.get(..2)?.try_into().ok()?) as usize;
+        let payload = packet.get(2 + name_len + 4..)?;
+
+        let id_len = u16::from_be_bytes(payload.get(..2)?.try_into().ok()?) as usize;
+        let client_id = payload.get(2..2 + id_len)?;
+
+        Some(Protocol::Mqtt {
+            client_id: String::from_utf8_lossy(client_id).into_owned(),
+        })
+    }
+
+    fn postgres(data: &[u8]) -> Option<Protocol> {
+        let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+        let code = u32::from_be_bytes(data.get(4..8)?.try_into().ok()?);
+
+        match code {
+            Self::PG_SSL_REQUEST => Some(Protocol::Postgres { user: None }),
+            Self::PG_PROTOCOL_V3 => {
+                let params = data.get(8..len)?;
+                let mut it = params.split(|&b| b == 0);
+
+                while let Some(name) = it.next() {
+                    let value = it.next()?;
+
+                    if name == b"user" {
+                        return Some(Protocol::Postgres {
+                            user: Some(String::from_utf8_lossy(value).into_owned()),
+                        });
+                    }
+                }
+
+                Some(Protocol::Postgres { user: None })
+            }
+            _ => Some(Protocol::Unknown),
+        }
+    }
+}
@@ -4,12 +4,12 @@
 use std::ptr::NonNull;
 
 use anyhow::{anyhow, bail, Context};
-use merge::Merge as AutoMerge;
 
 use ngx_mod::{
     http::{self, Module as _},
+    merge::MergeError,
     rt::{
-        core::{conf::Unset, CmdRef, ConfRef, ConnRef},
+        core::{CmdRef, ConfRef, ConnRef},
         event::{FreePeerFn, GetPeerFn, PeerConnRef},
         ffi,
         http::{upstream, RequestRef},
@@ -25,42 +25,22 @@ struct Custom;
 impl Module for Custom {}
 
 impl http::Module for Custom {
-    type Error = ();
+    type Error = MergeError;
     type MainConf = ();
     type SrvConf = SrvConfig;
     type LocConf = ();
 }
 
-#[derive(Clone, Debug, AutoMerge, Conf)]
+#[derive(Clone, Debug, Conf, Merge)]
 #[conf(http::upstream, default = unset)]
 struct SrvConfig {
     #[directive(name = "custom", args(0, 1), set = ngx_http_upstream_custom)]
-    #[merge(strategy = overwrite_unset)]
     max: usize,
     original_init_upstream: Option<upstream::InitFn>,
     original_init_peer: Option<upstream::InitPeerFn>,
 }
 
-fn overwrite_unset<T>(left: &mut T, right: T)
-where
-    T: Sized + PartialEq + Unset,
-{
-    if left.is_unset() {
-        *left = right;
-    }
-}
-
-impl Merge for SrvConfig {
-    type Error = ();
-
-    fn merge(&mut self, prev: &SrvConfig) -> Result<(), ()> {
-        merge::Merge::merge(self, prev.clone());
-
-        Ok(())
-    }
-}
-
-#[native_setter(name = ngx_http_upstream_custom, log = cf)]
+#[native_setter(name = ngx_http_upstream_custom)]
 fn set_custom(cf: &ConfRef, _cmd: &CmdRef, conf: &mut SrvConfig) -> anyhow::Result<()> {
     notice!(cf, "CUSTOM init module");
 
@@ -68,11 +48,11 @@ fn set_custom(cf: &ConfRef, _cmd: &CmdRef, conf: &mut SrvConfig) -> anyhow::Resu
         let n = s
             .to_str()?
             .parse()
-            .context("expect `custom` value to be a number")?;
+            .with_context(|| format!("invalid value \"{}\"", s))?;
         if n > 0 {
             conf.max = n;
         } else {
-            bail!("expect `custom` value to be greater than 0");
+            bail!("invalid value \"{}\", it must be greater than 0", s);
         }
     }
 
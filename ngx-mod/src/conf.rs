@@ -1,6 +1,12 @@
 use std::mem;
 
-use crate::rt::ffi;
+use crate::{
+    merge::MergeReport,
+    rt::{
+        core::{ConfExt, ConfRef, Logger},
+        ffi,
+    },
+};
 
 pub trait UnsafeConf {
     type T: Copy;
@@ -14,6 +20,35 @@ impl UnsafeConf for () {
     const COMMANDS: [ffi::ngx_command_t; 0] = [];
 }
 
+/// Reports the error of merging the configuration as `nginx: [emerg] ...`,
+/// unless it has been reported by the module.
+pub(crate) fn merge_failed<E: MergeReport>(cf: &ConfRef, err: E) {
+    let msg = err.message();
+
+    if !msg.is_empty() {
+        Logger::emerg(cf, msg);
+    }
+}
+
+/// Sets the default values of the unset directives, after merging with the previous level,
+/// the error is reported as `nginx: [emerg] ...` to the configuration.
+pub(crate) fn init_defaults<T: ConfExt>(cf: &ConfRef, conf: &mut T) -> Result<(), ()> {
+    conf.init_defaults(cf).map_err(|err| {
+        let msg = err.to_string();
+
+        if !msg.is_empty() {
+            Logger::emerg(cf, msg);
+        }
+    })
+}
+
+/// Checks the required directives at the final level of the configuration,
+/// the error is reported as `nginx: [emerg] "name" directive is not set in <file>:<line>`.
+pub(crate) fn check_required<T: ConfExt>(cf: &ConfRef, conf: &T) -> Result<(), ()> {
+    conf.check_required()
+        .map_err(|err| Logger::emerg(cf, err.to_string()))
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! const_concat {
@@ -6,8 +6,9 @@ use std::{
 use foreign_types::ForeignTypeRef;
 
 use crate::{
+    conf::{check_required, init_defaults},
     rt::{
-        core::{CycleRef, NGX_CONF_ERROR, NGX_CONF_OK},
+        core::{Conf, ConfExt, CycleRef, NGX_CONF_ERROR, NGX_CONF_OK},
         ffi,
     },
     Merge,
@@ -36,14 +37,24 @@ impl<T: Module> UnsafeModule for T {
     }
 
     unsafe extern "C" fn init_conf(cycle: *mut ffi::ngx_cycle_t, conf: *mut c_void) -> *mut c_char {
-        <T as Module>::init_conf(CycleRef::from_ptr(cycle), &mut *conf.cast())
-            .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+        let cycle = CycleRef::from_ptr(cycle);
+        let conf = &mut *conf.cast::<T::Conf>();
+        let cf = Conf::with_cycle(cycle);
+
+        if init_defaults(&cf, conf)
+            .and_then(|_| check_required(&cf, conf))
+            .is_err()
+        {
+            return NGX_CONF_ERROR;
+        }
+
+        <T as Module>::init_conf(cycle, conf).map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
 }
 
 pub trait Module: crate::Module {
     type Error: From<<Self::Conf as Merge>::Error>;
-    type Conf: Default + Merge;
+    type Conf: Default + Merge + ConfExt;
 
     /// Create the configuration.
     fn create_conf(cycle: &CycleRef) -> Option<&mut Self::Conf> {
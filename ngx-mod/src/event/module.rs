@@ -0,0 +1,90 @@
+use std::{
+    ffi::{c_char, c_void},
+    ptr,
+};
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    conf::{check_required, init_defaults},
+    rt::{
+        core::{Conf, ConfExt, CycleRef, NGX_CONF_ERROR, NGX_CONF_OK},
+        event, ffi,
+    },
+    Merge,
+};
+
+/// The event actions of a module which is not an event method.
+pub const NO_ACTIONS: ffi::ngx_event_actions_t = ffi::ngx_event_actions_t {
+    add: None,
+    del: None,
+    enable: None,
+    disable: None,
+    add_conn: None,
+    del_conn: None,
+    notify: None,
+    process_events: None,
+    init: None,
+    done: None,
+};
+
+pub trait UnsafeModule {
+    /// Create the configuration.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn create_conf(cycle: *mut ffi::ngx_cycle_t) -> *mut c_void;
+
+    /// Initialize the configuration.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn init_conf(cycle: *mut ffi::ngx_cycle_t, conf: *mut c_void) -> *mut c_char;
+}
+
+impl<T: Module> UnsafeModule for T {
+    unsafe extern "C" fn create_conf(cycle: *mut ffi::ngx_cycle_t) -> *mut c_void {
+        <T as Module>::create_conf(CycleRef::from_ptr(cycle))
+            .map_or_else(ptr::null_mut, |p| p as *mut _ as *mut _)
+    }
+
+    unsafe extern "C" fn init_conf(cycle: *mut ffi::ngx_cycle_t, conf: *mut c_void) -> *mut c_char {
+        let cycle = CycleRef::from_ptr(cycle);
+        let conf = &mut *conf.cast::<T::Conf>();
+        let cf = Conf::with_cycle(cycle);
+
+        if init_defaults(&cf, conf)
+            .and_then(|_| check_required(&cf, conf))
+            .is_err()
+        {
+            return NGX_CONF_ERROR;
+        }
+
+        <T as Module>::init_conf(cycle, conf).map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+    }
+}
+
+pub trait Module: crate::Module {
+    type Error: From<<Self::Conf as Merge>::Error>;
+    type Conf: Default + Merge + ConfExt;
+
+    /// The event actions, only implemented by the event methods like `epoll` or `kqueue`.
+    const ACTIONS: ffi::ngx_event_actions_t = NO_ACTIONS;
+
+    /// Create the configuration.
+    fn create_conf(cycle: &CycleRef) -> Option<&mut Self::Conf> {
+        cycle.pool().allocate_default()
+    }
+
+    /// Initialize the configuration.
+    fn init_conf(_cycle: &CycleRef, _conf: &mut Self::Conf) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Returns the configuration of the module in the `events` block.
+    fn conf(cycle: &CycleRef) -> Option<&Self::Conf> {
+        event::conf(cycle, Self::module())
+    }
+}
@@ -1,3 +1,9 @@
 mod module;
+mod phase;
+mod registry;
+mod var;
 
 pub use self::module::{Module, UnsafeModule};
+pub use self::phase::{PhaseHandler, RawPhaseHandler};
+pub use self::registry::{Registered, MODULES, PHASE_HANDLERS, VARIABLES};
+pub use self::var::{Variable, VariableValue};
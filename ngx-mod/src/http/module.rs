@@ -6,14 +6,20 @@ use std::{
 use foreign_types::ForeignTypeRef;
 
 use crate::{
+    conf::{check_required, init_defaults, merge_failed},
+    merge::MergeReport,
     rt::{
-        core::{Code, ConfContext, ConfRef, CycleRef, NGX_CONF_ERROR, NGX_CONF_OK},
+        core::{
+            Code, ConfContext, ConfExt, ConfRef, CycleRef, Logger, Str, NGX_CONF_ERROR, NGX_CONF_OK,
+        },
         ffi,
-        http::{self, ConfContextRef, ModuleContext},
+        http::{self, core, ConfContextRef, ModuleContext},
     },
     Merge,
 };
 
+use super::{PHASE_HANDLERS, VARIABLES};
+
 pub trait UnsafeModule {
     /// A pre-configuration callback
     ///
@@ -83,14 +89,20 @@ pub trait UnsafeModule {
 
 impl<T: Module> UnsafeModule for T {
     unsafe extern "C" fn preconfiguration(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t {
-        <T as Module>::preconfiguration(ConfRef::from_ptr(cf))
+        let cf = ConfRef::from_ptr(cf);
+
+        <T as Module>::add_variables(cf)
+            .and_then(|_| <T as Module>::preconfiguration(cf))
             .err()
             .unwrap_or(Code::OK)
             .into()
     }
 
     unsafe extern "C" fn postconfiguration(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t {
-        <T as Module>::postconfiguration(ConfRef::from_ptr(cf))
+        let cf = ConfRef::from_ptr(cf);
+
+        <T as Module>::postconfiguration(cf)
+            .and_then(|_| <T as Module>::add_phase_handlers(cf))
             .err()
             .unwrap_or(Code::OK)
             .into()
@@ -105,8 +117,17 @@ impl<T: Module> UnsafeModule for T {
         cf: *mut ffi::ngx_conf_t,
         conf: *mut c_void,
     ) -> *mut c_char {
-        <T as Module>::init_main_conf(ConfRef::from_ptr(cf), &mut *conf.cast())
-            .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::MainConf>();
+
+        if init_defaults(cf, conf)
+            .and_then(|_| check_required(cf, conf))
+            .is_err()
+        {
+            return NGX_CONF_ERROR;
+        }
+
+        <T as Module>::init_main_conf(cf, conf).map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
 
     unsafe extern "C" fn create_srv_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void {
@@ -119,7 +140,13 @@ impl<T: Module> UnsafeModule for T {
         prev: *mut c_void,
         conf: *mut c_void,
     ) -> *mut c_char {
-        <T as Module>::merge_srv_conf(ConfRef::from_ptr(cf), &*prev.cast(), &mut *conf.cast())
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::SrvConf>();
+
+        <T as Module>::merge_srv_conf(cf, &*prev.cast(), conf)
+            .map_err(|err| merge_failed(cf, err))
+            .and_then(|_| init_defaults(cf, conf))
+            .and_then(|_| check_required(cf, conf))
             .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
 
@@ -133,16 +160,38 @@ impl<T: Module> UnsafeModule for T {
         prev: *mut c_void,
         conf: *mut c_void,
     ) -> *mut c_char {
-        <T as Module>::merge_loc_conf(ConfRef::from_ptr(cf), &*prev.cast(), &mut *conf.cast())
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::LocConf>();
+
+        <T as Module>::merge_loc_conf(cf, &*prev.cast(), conf)
+            .map_err(|err| merge_failed(cf, err))
+            .and_then(|_| init_defaults(cf, conf))
+            .and_then(|_| {
+                if is_location(cf) {
+                    check_required(cf, conf)
+                } else {
+                    Ok(())
+                }
+            })
             .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
 }
 
+/// Returns `true` if the location configuration is merged into a location,
+/// rather than the server level which is merged with the `http` level before its locations.
+fn is_location(cf: &ConfRef) -> bool {
+    cf.as_http_context()
+        .and_then(core::loc_conf)
+        .is_some_and(|clcf| !clcf.name().is_empty())
+}
+
 pub trait Module: crate::Module {
-    type Error: From<<Self::SrvConf as Merge>::Error> + From<<Self::LocConf as Merge>::Error>;
-    type MainConf: Default;
-    type SrvConf: Default + Merge;
-    type LocConf: Default + Merge;
+    type Error: From<<Self::SrvConf as Merge>::Error>
+        + From<<Self::LocConf as Merge>::Error>
+        + MergeReport;
+    type MainConf: Default + ConfExt;
+    type SrvConf: Default + Merge + ConfExt;
+    type LocConf: Default + Merge + ConfExt;
 
     fn preconfiguration(_cf: &ConfRef) -> Result<(), Code> {
         Ok(())
@@ -184,6 +233,69 @@ pub trait Module: crate::Module {
         conf.merge(prev).map_err(Self::Error::from)
     }
 
+    /// Registers the variables defined with the `#[variable(..)]` attribute
+    /// which belong to the module, see [`Registered::belongs_to`](super::Registered::belongs_to).
+    fn add_variables(cf: &ConfRef) -> Result<(), Code> {
+        if let Some(v) = VARIABLES.iter().find(|v| v.is_ambiguous::<Self>()) {
+            Logger::emerg(
+                cf,
+                format!(
+                    "the `#[variable]` \"{}\" should specify `module = ...`, \
+                     the crate \"{}\" defines more HTTP modules",
+                    Str::from(v.item.name),
+                    v.krate
+                ),
+            );
+
+            return Err(Code::ERROR);
+        }
+
+        cf.add_variables(
+            VARIABLES
+                .iter()
+                .filter(|v| v.belongs_to::<Self>())
+                .map(|v| v.item),
+        )
+        .map_err(|_| Code::ERROR)
+    }
+
+    /// Registers the phase handlers defined with the `#[phase_handler(..)]` attribute
+    /// which belong to the module, see [`Registered::belongs_to`](super::Registered::belongs_to).
+    fn add_phase_handlers(cf: &ConfRef) -> Result<(), Code> {
+        if let Some(h) = PHASE_HANDLERS.iter().find(|h| h.is_ambiguous::<Self>()) {
+            Logger::emerg(
+                cf,
+                format!(
+                    "the `#[phase_handler]` should specify `module = ...`, \
+                     the crate \"{}\" defines more HTTP modules",
+                    h.krate
+                ),
+            );
+
+            return Err(Code::ERROR);
+        }
+
+        let mut handlers = PHASE_HANDLERS
+            .iter()
+            .filter(|h| h.belongs_to::<Self>())
+            .peekable();
+
+        if handlers.peek().is_none() {
+            return Ok(());
+        }
+
+        let cmcf = cf
+            .as_http_context()
+            .and_then(core::main_conf_mut)
+            .ok_or(Code::ERROR)?;
+
+        for &(phase, h) in handlers.map(|h| &h.item) {
+            cmcf.push_handler(phase, h)?;
+        }
+
+        Ok(())
+    }
+
     fn conf_ctx(cycle: &CycleRef) -> Option<&ConfContextRef> {
         cycle.conf_ctx(Self::module())
     }
@@ -0,0 +1,38 @@
+use foreign_types::ForeignTypeRef;
+
+use crate::rt::{
+    ffi,
+    http::{
+        core::{PhaseResult, Phases},
+        HandlerFn, RequestRef,
+    },
+    NativeCallback,
+};
+
+/// A phase handler registered to the phase with its native callback.
+pub type RawPhaseHandler = (Phases, <HandlerFn as NativeCallback>::CType);
+
+/// A typed handler of the request processing phase.
+///
+/// Usually implemented with the `#[phase_handler(..)]` attribute,
+/// which registers the handler after the `postconfiguration` callback of the module,
+/// see [`Registered::belongs_to`](super::Registered::belongs_to).
+pub trait PhaseHandler {
+    /// The phase which the handler is registered to.
+    const PHASE: Phases;
+
+    /// The handler with its native callback.
+    const HANDLER: RawPhaseHandler = (Self::PHASE, Self::native_handler);
+
+    /// Handles the request in the phase.
+    fn handle(req: &RequestRef) -> PhaseResult;
+
+    /// A native handler of the phase
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn native_handler(r: *mut ffi::ngx_http_request_t) -> ffi::ngx_int_t {
+        Self::handle(RequestRef::from_ptr(r)).into()
+    }
+}
@@ -0,0 +1,58 @@
+use std::ptr;
+
+use linkme::distributed_slice;
+
+use crate::{
+    rt::{core::ModuleRef, http::RawVar},
+    ModuleMetadata,
+};
+
+use super::RawPhaseHandler;
+
+/// The crate names of the HTTP modules defined with `#[derive(Module)]`.
+#[distributed_slice]
+pub static MODULES: [&'static str];
+
+/// The phase handlers defined with the `#[phase_handler(..)]` attribute.
+#[distributed_slice]
+pub static PHASE_HANDLERS: [Registered<RawPhaseHandler>];
+
+/// The variables defined with the `#[variable(..)]` attribute.
+#[distributed_slice]
+pub static VARIABLES: [Registered<RawVar>];
+
+/// An item defined with the attribute, which is collected at link time
+/// and registered by the module it belongs to.
+pub struct Registered<T> {
+    /// The name of the crate which defines the item.
+    pub krate: &'static str,
+
+    /// The module specified with `module = ...`,
+    /// otherwise the item belongs to the only HTTP module of the crate.
+    pub module: Option<fn() -> &'static ModuleRef>,
+
+    pub item: T,
+}
+
+unsafe impl<T> Sync for Registered<T> {}
+
+impl<T> Registered<T> {
+    /// Returns `true` if the item belongs to the module.
+    pub fn belongs_to<M: ModuleMetadata + ?Sized>(&self) -> bool {
+        match self.module {
+            Some(module) => ptr::eq(module(), M::module()),
+            None => self.krate == M::crate_name() && modules_of(self.krate) == 1,
+        }
+    }
+
+    /// Returns `true` if the item doesn't specify its module,
+    /// but the crate of the module defines more HTTP modules.
+    pub fn is_ambiguous<M: ModuleMetadata + ?Sized>(&self) -> bool {
+        self.module.is_none() && self.krate == M::crate_name() && modules_of(self.krate) > 1
+    }
+}
+
+/// Returns the number of HTTP modules defined in the crate.
+fn modules_of(krate: &str) -> usize {
+    MODULES.iter().filter(|&&m| m == krate).count()
+}
@@ -0,0 +1,100 @@
+use std::{borrow::Cow, ptr, slice};
+
+use crate::rt::{
+    core::{Code, Str},
+    http::{RawVar, RequestRef, ValueRef},
+};
+
+/// A typed variable definition.
+///
+/// Usually implemented with the `#[variable(..)]` attribute,
+/// which registers the variable before the `preconfiguration` callback of the module in the crate.
+///
+/// A variable with `#[variable(set = f)]` is changeable, e.g. by the `set` directive,
+/// which calls `fn f(req: &RequestRef, value: &[u8])` with the new value.
+pub trait Variable {
+    /// The variable with its native handlers.
+    const VARIABLE: RawVar;
+
+    /// Copies the value to the request pool and binds it to the variable value,
+    /// or marks the variable as not found.
+    fn bind<V>(req: &RequestRef, val: &mut ValueRef, v: Option<&V>) -> Result<(), Code>
+    where
+        V: VariableValue + ?Sized,
+    {
+        let Some(v) = v else {
+            val.set_not_found(true);
+
+            return Ok(());
+        };
+
+        let b = v.as_bytes();
+
+        unsafe {
+            let p = req.pool().pnalloc(b.len()).cast::<u8>();
+
+            if p.is_null() {
+                return Err(Code::ERROR);
+            }
+
+            ptr::copy_nonoverlapping(b.as_ptr(), p, b.len());
+
+            val.set_value(slice::from_raw_parts(p, b.len()));
+        }
+
+        Ok(())
+    }
+}
+
+/// A value returned by the variable handler.
+pub trait VariableValue {
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl VariableValue for str {
+    fn as_bytes(&self) -> &[u8] {
+        str::as_bytes(self)
+    }
+}
+
+impl VariableValue for [u8] {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl VariableValue for String {
+    fn as_bytes(&self) -> &[u8] {
+        String::as_bytes(self)
+    }
+}
+
+impl VariableValue for Vec<u8> {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl VariableValue for Str {
+    fn as_bytes(&self) -> &[u8] {
+        Str::as_bytes(self)
+    }
+}
+
+impl<T> VariableValue for Cow<'_, T>
+where
+    T: VariableValue + ToOwned + ?Sized,
+{
+    fn as_bytes(&self) -> &[u8] {
+        self.as_ref().as_bytes()
+    }
+}
+
+impl<T> VariableValue for &T
+where
+    T: VariableValue + ?Sized,
+{
+    fn as_bytes(&self) -> &[u8] {
+        (*self).as_bytes()
+    }
+}
@@ -1,16 +1,23 @@
+pub extern crate linkme;
 pub extern crate memoffset;
 pub extern crate ngx_rt as rt;
 
-pub use ::ngx_mod_derive::{Conf, Module};
+#[cfg(feature = "http")]
+pub use ::ngx_mod_derive::{phase_handler, variable};
+pub use ::ngx_mod_derive::{Conf, Merge, Module, NgxEnum};
 
 #[macro_use]
 pub mod conf;
 pub mod core;
-mod merge;
+#[cfg(feature = "event")]
+pub mod event;
+pub mod merge;
 mod module;
 
 #[cfg(feature = "http")]
 pub mod http;
+#[cfg(feature = "mail")]
+pub mod mail;
 #[cfg(feature = "stream")]
 pub mod stream;
 
@@ -0,0 +1,3 @@
+mod module;
+
+pub use self::module::{Module, UnsafeModule};
@@ -0,0 +1,123 @@
+use std::{
+    ffi::{c_char, c_void},
+    ptr,
+};
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    conf::{check_required, init_defaults, merge_failed},
+    merge::MergeReport,
+    rt::{
+        core::{ConfExt, ConfRef, NGX_CONF_ERROR, NGX_CONF_OK},
+        ffi,
+    },
+    Merge,
+};
+
+pub trait UnsafeModule {
+    /// A callback for allocations and initializations of configurations for the main block configuration
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn create_main_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void;
+
+    /// A callback to set the configuration based on the directives supplied in the configuration files
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn init_main_conf(cf: *mut ffi::ngx_conf_t, conf: *mut c_void)
+        -> *mut c_char;
+
+    /// A callback for allocations and initializations of configurations for the server block configuration
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn create_srv_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void;
+
+    /// A callback to merge the server block configuration with the main block
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn merge_srv_conf(
+        cf: *mut ffi::ngx_conf_t,
+        prev: *mut c_void,
+        conf: *mut c_void,
+    ) -> *mut c_char;
+}
+
+impl<T: Module> UnsafeModule for T {
+    unsafe extern "C" fn create_main_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void {
+        <T as Module>::create_main_conf(ConfRef::from_ptr(cf))
+            .map_or_else(ptr::null_mut, |p| p as *mut _ as *mut _)
+    }
+
+    unsafe extern "C" fn init_main_conf(
+        cf: *mut ffi::ngx_conf_t,
+        conf: *mut c_void,
+    ) -> *mut c_char {
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::MainConf>();
+
+        if init_defaults(cf, conf)
+            .and_then(|_| check_required(cf, conf))
+            .is_err()
+        {
+            return NGX_CONF_ERROR;
+        }
+
+        <T as Module>::init_main_conf(cf, conf).map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+    }
+
+    unsafe extern "C" fn create_srv_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void {
+        <T as Module>::create_srv_conf(ConfRef::from_ptr(cf))
+            .map_or_else(ptr::null_mut, |p| p as *mut _ as *mut _)
+    }
+
+    unsafe extern "C" fn merge_srv_conf(
+        cf: *mut ffi::ngx_conf_t,
+        prev: *mut c_void,
+        conf: *mut c_void,
+    ) -> *mut c_char {
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::SrvConf>();
+
+        <T as Module>::merge_srv_conf(cf, &*prev.cast(), conf)
+            .map_err(|err| merge_failed(cf, err))
+            .and_then(|_| init_defaults(cf, conf))
+            .and_then(|_| check_required(cf, conf))
+            .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+    }
+}
+
+pub trait Module: crate::Module {
+    type Error: From<<Self::MainConf as Merge>::Error>
+        + From<<Self::SrvConf as Merge>::Error>
+        + MergeReport;
+    type MainConf: Default + Merge + ConfExt;
+    type SrvConf: Default + Merge + ConfExt;
+
+    fn create_main_conf(cf: &ConfRef) -> Option<&mut Self::MainConf> {
+        cf.pool().allocate_default()
+    }
+
+    fn init_main_conf(_cf: &ConfRef, _conf: &mut Self::MainConf) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn create_srv_conf(cf: &ConfRef) -> Option<&mut Self::SrvConf> {
+        cf.pool().allocate_default()
+    }
+
+    fn merge_srv_conf(
+        _cf: &ConfRef,
+        prev: &Self::SrvConf,
+        conf: &mut Self::SrvConf,
+    ) -> Result<(), Self::Error> {
+        conf.merge(prev).map_err(Self::Error::from)
+    }
+}
@@ -1,3 +1,16 @@
+//! Merging the configuration down through each level.
+//!
+//! The functions in this module are the strategies of `#[derive(Merge)]`,
+//! which can be specified per field with `#[merge(strategy = ...)]`.
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use crate::rt::core::{
+    conf::{MultiMerge, MultiValue},
+    Unset,
+};
+
 /// The `Merge` trait provides a method for merging configuration down through each level.
 pub trait Merge {
     type Error;
@@ -13,3 +26,126 @@ impl Merge for () {
         Ok(())
     }
 }
+
+/// The error of merging configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// The value of the field is invalid.
+    Invalid(&'static str, String),
+
+    /// The merging failed without a reason.
+    Failed,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::Invalid(field, reason) => write!(f, "`{}` is invalid, {}", field, reason),
+            MergeError::Failed => f.write_str("merge failed"),
+        }
+    }
+}
+
+impl StdError for MergeError {}
+
+/// The error of the module merging the configuration,
+/// which is reported as `nginx: [emerg] ...` to the configuration.
+pub trait MergeReport {
+    /// Returns the reported message, an empty message means the error has been reported.
+    fn message(&self) -> String;
+}
+
+impl MergeReport for () {
+    fn message(&self) -> String {
+        String::new()
+    }
+}
+
+impl MergeReport for MergeError {
+    fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Converts the error of the configuration which merges with `()`, e.g. the unit configuration.
+impl From<()> for MergeError {
+    fn from(_: ()) -> Self {
+        MergeError::Failed
+    }
+}
+
+/// Takes the previous value if the value is unset, like `ngx_conf_merge_value`.
+///
+/// This is the default strategy of `#[derive(Merge)]`.
+pub fn unset<T: Unset + Clone>(value: &mut T, prev: &T) -> Result<(), MergeError> {
+    if value.is_unset() {
+        *value = prev.clone();
+    }
+
+    Ok(())
+}
+
+/// Always keeps the value at this level.
+pub fn skip<T>(_value: &mut T, _prev: &T) -> Result<(), MergeError> {
+    Ok(())
+}
+
+/// Merges the nested configuration with the previous one.
+pub fn nested<T>(value: &mut T, prev: &T) -> Result<(), MergeError>
+where
+    T: Merge,
+    MergeError: From<T::Error>,
+{
+    value.merge(prev).map_err(MergeError::from)
+}
+
+/// Takes the previous value if the value is `false`.
+pub fn overwrite_false(value: &mut bool, prev: &bool) -> Result<(), MergeError> {
+    if !*value {
+        *value = *prev;
+    }
+
+    Ok(())
+}
+
+/// Takes the previous value if the value is `true`.
+pub fn overwrite_true(value: &mut bool, prev: &bool) -> Result<(), MergeError> {
+    if *value {
+        *value = *prev;
+    }
+
+    Ok(())
+}
+
+/// Takes the previous value if the value is empty, e.g. a `String`.
+pub fn overwrite_empty<T>(value: &mut T, prev: &T) -> Result<(), MergeError>
+where
+    T: Default + PartialEq + Clone,
+{
+    if *value == T::default() {
+        *value = prev.clone();
+    }
+
+    Ok(())
+}
+
+/// Inherits the previous values of the repeatable directive if none is set at this level.
+pub fn inherit<T: MultiValue>(value: &mut T, prev: &T) -> Result<(), MergeError> {
+    value.merge_multi(prev, MultiMerge::Inherit);
+
+    Ok(())
+}
+
+/// Appends the values of the repeatable directive to the previous values.
+pub fn append<T: MultiValue>(value: &mut T, prev: &T) -> Result<(), MergeError> {
+    value.merge_multi(prev, MultiMerge::Append);
+
+    Ok(())
+}
+
+/// Never inherits the previous values of the repeatable directive.
+pub fn replace<T: MultiValue>(value: &mut T, prev: &T) -> Result<(), MergeError> {
+    value.merge_multi(prev, MultiMerge::Replace);
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use foreign_types::ForeignTypeRef;
-use ngx_rt::core::{Cmds, Code, ModuleRef};
+use ngx_rt::core::{Cmds, Code, DirectiveDoc, ModuleRef};
 
 use crate::rt::{
     core::{CycleRef, LogRef},
@@ -139,5 +139,16 @@ pub trait Module: ModuleMetadata {
 pub trait ModuleMetadata {
     fn module() -> &'static ModuleRef;
 
+    /// Returns the name of the crate which defines the module.
+    fn crate_name() -> &'static str;
+
     fn commands() -> Cmds<'static>;
+
+    /// Returns the reference documentation of the directives.
+    fn directives() -> Vec<DirectiveDoc>;
+
+    /// Returns the module and its directives described in JSON, one object per line.
+    ///
+    /// It is also exported as the `<module>_doc` symbol, which `cargo xtask doc` extracts from the built modules.
+    fn doc() -> &'static str;
 }
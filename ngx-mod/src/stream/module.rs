@@ -6,9 +6,16 @@ use std::{
 use foreign_types::ForeignTypeRef;
 
 use crate::{
+    conf::{check_required, init_defaults, merge_failed},
+    merge::MergeReport,
     rt::{
-        core::{Code, ConfRef, NGX_CONF_ERROR, NGX_CONF_OK},
+        core::{Code, ConfContext, ConfExt, ConfRef, CycleRef, NGX_CONF_ERROR, NGX_CONF_OK},
         ffi,
+        stream::{
+            self,
+            core::{main_conf_mut, Phases},
+            ConfContextRef, ModuleContext, Preread, SessionRef,
+        },
     },
     Merge,
 };
@@ -60,6 +67,13 @@ pub trait UnsafeModule {
         prev: *mut c_void,
         conf: *mut c_void,
     ) -> *mut c_char;
+
+    /// A handler of the preread phase
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    unsafe extern "C" fn preread_handler(s: *mut ffi::ngx_stream_session_t) -> ffi::ngx_int_t;
 }
 
 impl<T: Module> UnsafeModule for T {
@@ -86,8 +100,17 @@ impl<T: Module> UnsafeModule for T {
         cf: *mut ffi::ngx_conf_t,
         conf: *mut c_void,
     ) -> *mut c_char {
-        <T as Module>::init_main_conf(ConfRef::from_ptr(cf), &*conf.cast())
-            .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::MainConf>();
+
+        if init_defaults(cf, conf)
+            .and_then(|_| check_required(cf, conf))
+            .is_err()
+        {
+            return NGX_CONF_ERROR;
+        }
+
+        <T as Module>::init_main_conf(cf, conf).map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
 
     unsafe extern "C" fn create_srv_conf(cf: *mut ffi::ngx_conf_t) -> *mut c_void {
@@ -100,15 +123,27 @@ impl<T: Module> UnsafeModule for T {
         prev: *mut c_void,
         conf: *mut c_void,
     ) -> *mut c_char {
-        <T as Module>::merge_srv_conf(ConfRef::from_ptr(cf), &*prev.cast(), &mut *conf.cast())
+        let cf = ConfRef::from_ptr(cf);
+        let conf = &mut *conf.cast::<T::SrvConf>();
+
+        <T as Module>::merge_srv_conf(cf, &*prev.cast(), conf)
+            .map_err(|err| merge_failed(cf, err))
+            .and_then(|_| init_defaults(cf, conf))
+            .and_then(|_| check_required(cf, conf))
             .map_or(NGX_CONF_ERROR, |_| NGX_CONF_OK)
     }
+
+    unsafe extern "C" fn preread_handler(s: *mut ffi::ngx_stream_session_t) -> ffi::ngx_int_t {
+        <T as Module>::preread(SessionRef::from_ptr(s)).into()
+    }
 }
 
 pub trait Module: crate::Module {
-    type Error: From<<Self::MainConf as Merge>::Error> + From<<Self::SrvConf as Merge>::Error>;
-    type MainConf: Default + Merge;
-    type SrvConf: Default + Merge;
+    type Error: From<<Self::MainConf as Merge>::Error>
+        + From<<Self::SrvConf as Merge>::Error>
+        + MergeReport;
+    type MainConf: Default + Merge + ConfExt;
+    type SrvConf: Default + Merge + ConfExt;
 
     fn preconfiguration(_cf: &ConfRef) -> Result<(), Code> {
         Ok(())
@@ -122,7 +157,7 @@ pub trait Module: crate::Module {
         cf.pool().allocate_default()
     }
 
-    fn init_main_conf(_cf: &ConfRef, _conf: &Self::MainConf) -> Result<(), Self::Error> {
+    fn init_main_conf(_cf: &ConfRef, _conf: &mut Self::MainConf) -> Result<(), Self::Error> {
         Ok(())
     }
 
@@ -137,4 +172,78 @@ pub trait Module: crate::Module {
     ) -> Result<(), Self::Error> {
         conf.merge(prev).map_err(Self::Error::from)
     }
+
+    /// Inspects the client data buffered by the preread phase.
+    ///
+    /// The handler is called each time new data arrives, until it returns anything but
+    /// [`Preread::Again`], the `preread_buffer_size` is exhausted or `preread_timeout` expires.
+    fn preread(_s: &SessionRef) -> Preread {
+        Preread::Declined
+    }
+
+    /// Registers [`Module::preread`] as a handler of the preread phase.
+    ///
+    /// Usually called from the `postconfiguration` callback.
+    fn add_preread_handler(cf: &ConfRef) -> Result<(), Code>
+    where
+        Self: Sized,
+    {
+        cf.as_stream_context()
+            .and_then(main_conf_mut)
+            .ok_or(Code::ERROR)?
+            .push_handler(Phases::Preread, <Self as UnsafeModule>::preread_handler)
+    }
+
+    fn conf_ctx(cycle: &CycleRef) -> Option<&ConfContextRef> {
+        cycle.conf_ctx(Self::module())
+    }
+
+    fn module_ctx<M, T>(m: &M) -> Option<&T>
+    where
+        M: ModuleContext,
+    {
+        m.module_ctx(Self::module())
+    }
+
+    fn module_ctx_mut<M, T>(m: &M) -> Option<&mut T>
+    where
+        M: ModuleContext,
+    {
+        m.module_ctx_mut(Self::module())
+    }
+
+    fn set_module_ctx<M, T>(m: &M, ctx: &T)
+    where
+        M: ModuleContext,
+    {
+        m.set_module_ctx(Self::module(), ctx)
+    }
+
+    fn main_conf<T>(cf: &T) -> Option<&Self::MainConf>
+    where
+        T: stream::MainConf,
+    {
+        cf.main_conf(Self::module())
+    }
+
+    fn main_conf_mut<T>(cf: &T) -> Option<&mut Self::MainConf>
+    where
+        T: stream::MainConf,
+    {
+        cf.main_conf_mut(Self::module())
+    }
+
+    fn srv_conf<T>(cf: &T) -> Option<&Self::SrvConf>
+    where
+        T: stream::SrvConf,
+    {
+        cf.srv_conf(Self::module())
+    }
+
+    fn srv_conf_mut<T>(cf: &T) -> Option<&mut Self::SrvConf>
+    where
+        T: stream::SrvConf,
+    {
+        cf.srv_conf_mut(Self::module())
+    }
 }
@@ -0,0 +1,12 @@
+use ngx_mod::rt::native_setter;
+
+#[native_setter(log = cf)]
+fn set_foo(
+    cf: &ngx_mod::rt::core::ConfRef,
+    _cmd: &ngx_mod::rt::core::CmdRef,
+    _conf: &mut (),
+) -> Result<(), String> {
+    Err("is invalid".to_string())
+}
+
+fn main() {}
@@ -1,15 +1,20 @@
 use std::mem;
 
+use anyhow::bail;
 use ngx_mod::{
     rt::{
-        core::{ArrayRef, Str},
-        ngx_str,
+        core::{ArrayRef, CmdRef, ConfRef, Str},
+        native_setter, ngx_str,
     },
     Conf,
 };
 
 use foreign_types::ForeignTypeRef;
 
+mod util;
+
+use util::Fixture;
+
 #[derive(Clone, Debug, Conf, Default)]
 #[conf(http::upstream)]
 pub struct MainConf {
@@ -69,3 +74,58 @@ fn loc_conf() {
     assert_eq!(c.ngx_str, ngx_str!());
     assert_eq!(c.ngx_array, unsafe { mem::zeroed() });
 }
+
+#[derive(Clone, Debug, Default, Conf)]
+pub struct Logged {
+    #[directive(args(1..=2), set = set_level)]
+    pub level: isize,
+}
+
+#[native_setter]
+fn set_level(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Logged) -> anyhow::Result<()> {
+    match cf.args()[1].to_str()? {
+        "warn" => cf.warn("is noisy"),
+        "emerg" => {
+            cf.emerg("is broken");
+
+            // the error has been reported
+            bail!("")
+        }
+        "error" => bail!("is invalid"),
+        _ => {}
+    }
+
+    conf.level += 1;
+
+    Ok(())
+}
+
+#[test]
+fn log_directive() {
+    let fixture = Fixture::new();
+    let mut conf = Logged::default();
+
+    fixture.parse("level warn extra;", &mut conf).unwrap();
+
+    assert_eq!(conf.level, 1);
+    assert!(fixture.log().contains("[warn] "), "{}", fixture.log());
+    assert!(fixture
+        .log()
+        .contains("\"level\" directive is noisy: warn extra in test.conf:1"));
+
+    let fixture = Fixture::new();
+    let log = fixture.parse("level emerg;", &mut conf).unwrap_err();
+
+    assert!(log.contains("[emerg] "), "{}", log);
+    assert!(log.contains("\"level\" directive is broken: emerg in test.conf:1"));
+    assert_eq!(log.matches("\"level\" directive").count(), 1, "{}", log);
+
+    let fixture = Fixture::new();
+    let log = fixture.parse("level error 2;", &mut conf).unwrap_err();
+
+    assert!(
+        log.contains("\"level\" directive is invalid in test.conf:1"),
+        "{}",
+        log
+    );
+}
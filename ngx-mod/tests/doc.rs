@@ -0,0 +1,91 @@
+use ngx_mod::{
+    http,
+    merge::MergeError,
+    rt::core::{
+        conf::{DirectiveDoc, Flag},
+        ConfExt, MSec, Str,
+    },
+    Conf, Merge, Module, ModuleMetadata,
+};
+
+#[derive(Module)]
+#[module(type = http)]
+struct Doc;
+
+impl Module for Doc {}
+
+impl http::Module for Doc {
+    type Error = MergeError;
+    type MainConf = ();
+    type SrvConf = ();
+    type LocConf = LocConf;
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(http::main | http::server | http::location, default = unset)]
+pub struct LocConf {
+    /// Enables the module.
+    #[directive(name = "doc", args(1))]
+    pub enable: Flag,
+    /// Sets the timeout of the request,
+    /// which is the time between two successive reads.
+    #[directive(name = "doc_timeout", args(1))]
+    pub timeout: MSec,
+    #[directive(name = "doc_add_header", args(2, 3))]
+    pub header: Option<String>,
+    #[directive(name = "doc_mode", args(1), one_of = ["fast", "slow"], default = "fast")]
+    pub mode: Str,
+    /// Sets the size of the buffer.
+    #[directive(name = "doc_buffer_size", args(1), set = size, default = "8k")]
+    pub buffer_size: usize,
+}
+
+#[test]
+fn directives() {
+    assert_eq!(
+        LocConf::directives()[0],
+        DirectiveDoc {
+            name: "doc",
+            syntax: "doc on | off;",
+            default: None,
+            contexts: &["http", "server", "location"],
+            doc: "Enables the module.",
+        }
+    );
+
+    let directives = Doc::directives();
+
+    assert_eq!(
+        directives.iter().map(|d| d.syntax).collect::<Vec<_>>(),
+        [
+            "doc on | off;",
+            "doc_timeout time;",
+            "doc_add_header value1 value2 [value3];",
+            "doc_mode fast | slow;",
+            "doc_buffer_size size;",
+        ]
+    );
+    assert_eq!(
+        directives[1].doc,
+        "Sets the timeout of the request,\nwhich is the time between two successive reads."
+    );
+    assert_eq!(directives[1].default, None);
+    assert_eq!(directives[3].default, Some("doc_mode fast;"));
+    assert_eq!(directives[4].default, Some("doc_buffer_size 8k;"));
+}
+
+#[test]
+fn doc() {
+    let lines = Doc::doc().lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], r#"{"module":"ngx_doc_module","type":"http"}"#);
+    assert_eq!(
+        lines[1],
+        r#"{"name":"doc","syntax":"doc on | off;","default":null,"context":["http","server","location"],"doc":"Enables the module."}"#
+    );
+    assert_eq!(
+        lines[2],
+        r#"{"name":"doc_timeout","syntax":"doc_timeout time;","default":null,"context":["http","server","location"],"doc":"Sets the timeout of the request,\nwhich is the time between two successive reads."}"#
+    );
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "event")]
+
+use foreign_types::ForeignTypeRef;
+use ngx_mod::{
+    event,
+    merge::MergeError,
+    rt::core::{CycleRef, ModuleType, NGX_CONF_ERROR, NGX_CONF_OK},
+    Conf, Merge, Module, ModuleMetadata,
+};
+
+mod util;
+
+use util::Fixture;
+
+#[derive(Module)]
+#[module(type = event)]
+struct M;
+
+impl Module for M {}
+
+impl event::Module for M {
+    type Error = MergeError;
+    type Conf = EventConf;
+
+    fn init_conf(_cycle: &CycleRef, conf: &mut Self::Conf) -> Result<(), Self::Error> {
+        if conf.connections > 1024 {
+            return Err(MergeError::Invalid(
+                "connections",
+                "too many connections".into(),
+            ));
+        }
+
+        conf.connections *= 2;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(event, default = unset)]
+struct EventConf {
+    #[directive(args(1), default = "512")]
+    connections: isize,
+}
+
+#[test]
+fn event_module() {
+    assert_eq!(M::module().ty(), ModuleType::Event);
+    assert_eq!(M::commands().len(), 1);
+}
+
+#[test]
+fn init_conf() {
+    let fixture = Fixture::new();
+    let cycle = fixture.cycle().as_ptr();
+
+    unsafe {
+        let create = ngx_m_module_ctx.create_conf.unwrap();
+        let init = ngx_m_module_ctx.init_conf.unwrap();
+
+        let conf = create(cycle);
+        assert!(!conf.is_null());
+        assert_eq!(init(cycle, conf), NGX_CONF_OK);
+        assert_eq!((*conf.cast::<EventConf>()).connections, 1024);
+
+        let conf = create(cycle);
+        (*conf.cast::<EventConf>()).connections = 2048;
+        assert_eq!(init(cycle, conf), NGX_CONF_ERROR);
+    }
+}
+
+/// Parses the directives like in the `events` block, dispatched by `ngx_conf_handler()` to the module commands.
+#[cfg(feature = "static-link")]
+#[test]
+fn events_block() {
+    use std::{ffi::c_void, ptr};
+
+    use foreign_types::ForeignType;
+    use ngx_mod::rt::ffi;
+
+    let fixture = Fixture::new();
+    let cycle = fixture.cycle().as_ptr();
+
+    unsafe {
+        let mut modules = [ptr::addr_of_mut!(ngx_m_module), ptr::null_mut()];
+
+        ngx_m_module.ctx_index = 0;
+        (*cycle).modules = modules.as_mut_ptr();
+
+        let conf = ngx_m_module_ctx.create_conf.unwrap()(cycle);
+        let mut confs = [conf];
+        let ctx = confs.as_mut_ptr();
+
+        let parse = |text: &str, cmd_type: u32| {
+            let cf = fixture.conf(text);
+            let raw = &mut *cf.as_ptr();
+
+            raw.ctx = &ctx as *const _ as *mut c_void;
+            raw.module_type = ffi::NGX_EVENT_MODULE as _;
+            raw.cmd_type = cmd_type as _;
+
+            ffi::ngx_conf_parse(cf.as_ptr(), ptr::null_mut())
+        };
+
+        assert_eq!(parse("connections 100;", ffi::NGX_EVENT_CONF), NGX_CONF_OK);
+        assert_eq!(
+            ngx_m_module_ctx.init_conf.unwrap()(cycle, conf),
+            NGX_CONF_OK
+        );
+        assert_eq!((*conf.cast::<EventConf>()).connections, 200);
+
+        assert_eq!(
+            parse("connections 100;", ffi::NGX_MAIN_CONF),
+            NGX_CONF_ERROR
+        );
+        assert!(fixture
+            .log()
+            .contains("\"connections\" directive is not allowed here"));
+    }
+}
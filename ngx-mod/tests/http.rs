@@ -1,4 +1,19 @@
-use ngx_mod::{http, rt::core::ModuleType, Module, ModuleMetadata};
+use std::{borrow::Cow, mem::zeroed, sync::Mutex};
+
+use ngx_mod::{
+    http::{self, PhaseHandler, Variable},
+    phase_handler,
+    rt::{
+        core::{Code, ModuleType, Str},
+        ffi,
+        foreign_types::ForeignTypeRef,
+        http::{
+            core::{PhaseResult, Phases},
+            RequestRef, ValueRef,
+        },
+    },
+    variable, Module, ModuleMetadata,
+};
 
 #[derive(Module)]
 #[module(type = http)]
@@ -13,8 +28,99 @@ impl http::Module for M {
     type LocConf = ();
 }
 
+#[phase_handler(Access)]
+fn access(_req: &RequestRef) -> PhaseResult {
+    PhaseResult::Declined
+}
+
+#[phase_handler(Phases::Log, module = M)]
+fn log(_req: &RequestRef) -> Result<PhaseResult, Code> {
+    Err(Code::DECLINED)
+}
+
+#[variable(flags = NOCACHEABLE)]
+fn foo(_req: &RequestRef) -> Option<Cow<'static, str>> {
+    Some(Cow::Borrowed("foo"))
+}
+
+#[variable(name = "my_arg_", prefix, module = M)]
+fn my_arg(_req: &RequestRef, name: &str) -> Option<String> {
+    Some(name.to_uppercase())
+}
+
+static BAR: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+#[variable(set = set_bar)]
+fn bar(_req: &RequestRef) -> Option<Vec<u8>> {
+    Some(BAR.lock().unwrap().clone())
+}
+
+fn set_bar(_req: &RequestRef, value: &[u8]) {
+    *BAR.lock().unwrap() = value.to_vec();
+}
+
 #[test]
 fn module_metadata() {
     assert_eq!(M::module().ty(), ModuleType::Http);
     assert_eq!(M::commands().len(), 0);
 }
+
+#[test]
+fn phase_handlers() {
+    assert_eq!(access::PHASE, Phases::Access);
+    assert_eq!(log::PHASE, Phases::Log);
+
+    let mut phases = http::PHASE_HANDLERS
+        .iter()
+        .filter(|h| h.belongs_to::<M>())
+        .map(|h| h.item.0)
+        .collect::<Vec<_>>();
+
+    phases.sort();
+
+    assert_eq!(phases, [Phases::Access, Phases::Log]);
+    assert_eq!(M::crate_name(), "http");
+    assert_eq!(http::MODULES[..], ["http"]);
+    assert!(http::PHASE_HANDLERS.iter().all(|h| !h.is_ambiguous::<M>()));
+}
+
+#[test]
+fn variables() {
+    assert_eq!(Str::from(foo::VARIABLE.name), "foo");
+    assert_eq!(foo::VARIABLE.flags, ffi::NGX_HTTP_VAR_NOCACHEABLE as usize);
+    assert!(foo::VARIABLE.get_handler.is_some());
+
+    assert_eq!(Str::from(my_arg::VARIABLE.name), "my_arg_");
+    assert_eq!(my_arg::VARIABLE.flags, ffi::NGX_HTTP_VAR_PREFIX as usize);
+
+    assert_eq!(Str::from(bar::VARIABLE.name), "bar");
+    assert_eq!(bar::VARIABLE.flags, ffi::NGX_HTTP_VAR_CHANGEABLE as usize);
+
+    let mut names = http::VARIABLES
+        .iter()
+        .filter(|v| v.belongs_to::<M>())
+        .map(|v| Str::from(v.item.name).to_string())
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    assert_eq!(names, ["bar", "foo", "my_arg_"]);
+}
+
+#[test]
+fn set_variable() {
+    assert!(foo::VARIABLE.set_handler.is_none());
+
+    unsafe {
+        let r = Box::into_raw(Box::new(zeroed::<ffi::ngx_http_request_t>()));
+        let v = Box::into_raw(Box::new(zeroed::<ffi::ngx_http_variable_value_t>()));
+
+        ValueRef::from_ptr_mut(v).set_value("baz");
+        bar::VARIABLE.set_handler.unwrap()(r, v, 0);
+
+        drop(Box::from_raw(v));
+        drop(Box::from_raw(r));
+    }
+
+    assert_eq!(*BAR.lock().unwrap(), b"baz");
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "mail")]
+
+use foreign_types::ForeignType;
+use ngx_mod::{
+    mail,
+    merge::MergeError,
+    rt::core::{ConfRef, MSec, ModuleType, NGX_CONF_OK},
+    Conf, Merge, Module, ModuleMetadata,
+};
+
+mod util;
+
+use util::Fixture;
+
+#[derive(Module)]
+#[module(type = mail)]
+struct M;
+
+impl Module for M {}
+
+impl mail::Module for M {
+    type Error = MergeError;
+    type MainConf = MainConf;
+    type SrvConf = SrvConf;
+
+    fn init_main_conf(_cf: &ConfRef, conf: &mut Self::MainConf) -> Result<(), Self::Error> {
+        conf.workers *= 2;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(mail::main, default = unset)]
+struct MainConf {
+    #[directive(args(1), default = "3")]
+    workers: isize,
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(mail::server, default = unset)]
+struct SrvConf {
+    #[directive(args(1))]
+    timeout: MSec,
+}
+
+#[test]
+fn mail_module() {
+    assert_eq!(M::module().ty(), ModuleType::Mail);
+    assert_eq!(M::commands().len(), 2);
+}
+
+#[test]
+fn init_main_conf() {
+    let fixture = Fixture::new();
+    let cf = fixture.conf("");
+
+    unsafe {
+        let create = ngx_m_module_ctx.create_main_conf.unwrap();
+        let init = ngx_m_module_ctx.init_main_conf.unwrap();
+
+        let conf = create(cf.as_ptr());
+        assert!(!conf.is_null());
+        assert_eq!(init(cf.as_ptr(), conf), NGX_CONF_OK);
+        assert_eq!(
+            (*conf.cast::<MainConf>()).workers,
+            6,
+            "default doubled by the hook"
+        );
+
+        let conf = create(cf.as_ptr());
+        fixture
+            .parse("workers 4;", &mut *conf.cast::<MainConf>())
+            .unwrap();
+        assert_eq!(init(cf.as_ptr(), conf), NGX_CONF_OK);
+        assert_eq!(
+            (*conf.cast::<MainConf>()).workers,
+            8,
+            "directive doubled by the hook"
+        );
+    }
+}
+
+#[test]
+fn merge_srv_conf() {
+    let fixture = Fixture::new();
+    let cf = fixture.conf("");
+
+    unsafe {
+        let create = ngx_m_module_ctx.create_srv_conf.unwrap();
+        let merge = ngx_m_module_ctx.merge_srv_conf.unwrap();
+
+        let prev = create(cf.as_ptr());
+        let conf = create(cf.as_ptr());
+
+        fixture
+            .parse("timeout 5s;", &mut *prev.cast::<SrvConf>())
+            .unwrap();
+        assert_eq!(merge(cf.as_ptr(), prev, conf), NGX_CONF_OK);
+        assert_eq!((*conf.cast::<SrvConf>()).timeout, MSec::from(5000));
+    }
+}
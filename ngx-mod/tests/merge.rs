@@ -0,0 +1,294 @@
+use std::{collections::BTreeMap, ffi::c_void, mem::zeroed};
+
+use foreign_types::ForeignType;
+
+use ngx_mod::{
+    http,
+    merge::MergeError,
+    rt::{
+        core::{conf::Enum, ConfExt, MSec, Str, Unset, NGX_CONF_ERROR, NGX_CONF_OK},
+        ffi,
+    },
+    Conf, Merge, Module, NgxEnum,
+};
+
+mod util;
+
+use util::Fixture;
+
+#[derive(Module)]
+#[module(type = http)]
+struct M;
+
+impl Module for M {}
+
+impl http::Module for M {
+    type Error = MergeError;
+    type MainConf = ();
+    type SrvConf = Defaulted;
+    type LocConf = Required;
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(default = unset)]
+pub struct LocConf {
+    #[directive(args(1))]
+    #[merge(default = 60)]
+    pub max: isize,
+    #[directive(args(1))]
+    #[merge(default = MSec::from(5000))]
+    pub timeout: MSec,
+    #[directive(args(1))]
+    pub name: Option<String>,
+    #[directive(args(1))]
+    pub str: Str,
+    #[directive(args(2), multi(append))]
+    pub headers: BTreeMap<String, String>,
+    #[merge(skip)]
+    pub hits: usize,
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(default = unset)]
+pub struct Required {
+    #[directive(name = "backend", args(1), required)]
+    pub upstream: Option<String>,
+}
+
+#[derive(Clone, Debug, Conf)]
+#[conf(default = unset)]
+pub struct Defaulted {
+    #[directive(args(1), default = "5s")]
+    pub timeout: MSec,
+    #[directive(args(1), default = 3, deprecated = "use \"timeout\" instead")]
+    pub retries: isize,
+    #[directive(args(1), default = "slow")]
+    pub mode: Enum<Mode>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, NgxEnum)]
+pub enum Mode {
+    Fast = 1,
+    Slow = 2,
+}
+
+impl Merge for Defaulted {
+    type Error = MergeError;
+
+    fn merge(&mut self, prev: &Self) -> Result<(), MergeError> {
+        self.timeout.or_insert(prev.timeout);
+        self.retries.or_insert(prev.retries);
+        self.mode.or_insert(prev.mode);
+
+        if !self.retries.is_unset() && self.retries < 0 {
+            return Err(MergeError::Invalid(
+                "retries",
+                "must be positive".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Merge)]
+pub struct Flags {
+    #[merge(strategy = overwrite_false)]
+    pub enable: bool,
+    #[merge(strategy = overwrite_empty)]
+    pub label: String,
+    #[merge(strategy = nested)]
+    pub loc: Nested,
+    #[merge(strategy = positive)]
+    pub weight: isize,
+}
+
+#[derive(Clone, Debug, Default, Merge)]
+pub struct Nested {
+    pub value: Option<u32>,
+}
+
+fn positive(value: &mut isize, prev: &isize) -> Result<(), MergeError> {
+    if *value == 0 {
+        *value = *prev;
+    }
+
+    if *value < 0 {
+        Err(MergeError::Invalid(
+            "weight",
+            "must be positive".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn merge_unset() {
+    let prev = LocConf {
+        max: 10,
+        name: Some("prev".to_string()),
+        headers: BTreeMap::from([("X-Foo".to_string(), "foo".to_string())]),
+        hits: 1,
+        ..LocConf::default()
+    };
+    let mut conf = LocConf {
+        headers: BTreeMap::from([("X-Bar".to_string(), "bar".to_string())]),
+        ..LocConf::default()
+    };
+
+    conf.merge(&prev).unwrap();
+
+    assert_eq!(conf.max, 10);
+    assert_eq!(conf.timeout, MSec::from(5000));
+    assert_eq!(conf.name.as_deref(), Some("prev"));
+    assert_eq!(conf.str, Str::null());
+    assert_eq!(conf.headers.len(), 2);
+    assert_eq!(conf.hits, usize::MAX);
+}
+
+#[test]
+fn merge_strategy() {
+    let prev = Flags {
+        enable: true,
+        label: "prev".to_string(),
+        loc: Nested { value: Some(1) },
+        weight: 3,
+    };
+    let mut conf = Flags::default();
+
+    conf.merge(&prev).unwrap();
+
+    assert!(conf.enable);
+    assert_eq!(conf.label, "prev");
+    assert_eq!(conf.loc.value, Some(1));
+    assert_eq!(conf.weight, 3);
+
+    let mut conf = Flags {
+        weight: -1,
+        ..Flags::default()
+    };
+
+    assert_eq!(
+        conf.merge(&prev),
+        Err(MergeError::Invalid(
+            "weight",
+            "must be positive".to_string()
+        ))
+    );
+}
+
+#[test]
+fn merge_required() {
+    let prev = Required {
+        upstream: Some("backend".to_string()),
+    };
+    let mut conf = Required::default();
+
+    conf.merge(&prev).unwrap();
+
+    assert_eq!(conf.upstream.as_deref(), Some("backend"));
+    assert!(conf.check_required().is_ok());
+
+    let mut conf = Required::default();
+
+    conf.merge(&Required::default()).unwrap();
+
+    assert_eq!(
+        conf.check_required().unwrap_err().to_string(),
+        "\"backend\" directive is not set"
+    );
+}
+
+#[test]
+fn merge_location_only() {
+    let fixture = Fixture::new();
+    let cf = fixture.conf("");
+
+    unsafe {
+        let clcf: *mut ffi::ngx_http_core_loc_conf_t = Box::into_raw(Box::new(zeroed()));
+        let loc_conf = Box::into_raw(Box::new([clcf.cast::<c_void>()]));
+        let ctx: *mut ffi::ngx_http_conf_ctx_t = Box::into_raw(Box::new(zeroed()));
+
+        (*ctx).loc_conf = (*loc_conf).as_mut_ptr();
+        ffi::ngx_http_core_module.ctx_index = 0;
+        (*cf.as_ptr()).ctx = ctx.cast();
+        (*cf.as_ptr()).module_type = ffi::NGX_HTTP_MODULE as _;
+
+        let create = ngx_m_module_ctx.create_loc_conf.unwrap();
+        let merge = ngx_m_module_ctx.merge_loc_conf.unwrap();
+
+        let http = create(cf.as_ptr());
+        let srv = create(cf.as_ptr());
+
+        // the server level is merged with the `http` level before its locations
+        assert_eq!(merge(cf.as_ptr(), http, srv), NGX_CONF_OK);
+
+        (*clcf).name = (&Str::from("/")).into();
+
+        let loc = create(cf.as_ptr());
+
+        fixture
+            .parse("backend foo;", &mut *loc.cast::<Required>())
+            .unwrap();
+        assert_eq!(merge(cf.as_ptr(), srv, loc), NGX_CONF_OK);
+
+        let other = create(cf.as_ptr());
+
+        assert_eq!(merge(cf.as_ptr(), srv, other), NGX_CONF_ERROR);
+        assert!(fixture.log().contains("\"backend\" directive is not set"));
+    }
+}
+
+#[test]
+fn merge_defaults() {
+    let fixture = Fixture::new();
+    let cf = fixture.conf("");
+
+    unsafe {
+        let create = ngx_m_module_ctx.create_srv_conf.unwrap();
+        let merge = ngx_m_module_ctx.merge_srv_conf.unwrap();
+
+        let http = create(cf.as_ptr());
+        let srv = create(cf.as_ptr());
+
+        (*http.cast::<Defaulted>()).timeout = MSec::from(10000);
+
+        assert_eq!(merge(cf.as_ptr(), http, srv), NGX_CONF_OK);
+
+        let conf = &*srv.cast::<Defaulted>();
+
+        assert_eq!(conf.timeout, MSec::from(10000));
+        assert_eq!(conf.retries, 3);
+        // the enum values are kept in the `post` of the command
+        assert_eq!(conf.mode.get(), Some(Mode::Slow));
+        // the `post` handler is not called for the default value
+        assert!(!fixture.log().contains("deprecated"), "{}", fixture.log());
+    }
+}
+
+#[test]
+fn merge_report() {
+    let fixture = Fixture::new();
+    let cf = fixture.conf("");
+
+    unsafe {
+        let create = ngx_m_module_ctx.create_srv_conf.unwrap();
+        let merge = ngx_m_module_ctx.merge_srv_conf.unwrap();
+
+        let http = create(cf.as_ptr());
+        let srv = create(cf.as_ptr());
+
+        (*srv.cast::<Defaulted>()).retries = -1;
+
+        assert_eq!(merge(cf.as_ptr(), http, srv), NGX_CONF_ERROR);
+        // the error of the hand-written `Merge` is reported by the framework
+        assert!(
+            fixture
+                .log()
+                .contains("`retries` is invalid, must be positive"),
+            "{}",
+            fixture.log()
+        );
+    }
+}
@@ -1,10 +1,25 @@
-use std::fs;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use ngx_mod::{
-    rt::core::{conf, ArrayRef, Bufs, KeyValue, MSec, Sec, Str},
-    Conf,
+    rt::{
+        core::{
+            conf::{self, BitMask, Enum},
+            ArrayRef, Bufs, CmdRef, ConfRef, KeyValue, MSec, Sec, Str, Unset,
+        },
+        native_setter,
+    },
+    Conf, Merge,
 };
 
+mod util;
+
+use util::Fixture;
+
 #[derive(Clone, Debug, Conf)]
 #[conf(default = unset)]
 pub struct Conf<'a> {
@@ -28,16 +43,269 @@ pub struct Conf<'a> {
     pub sec: Sec,
     #[directive(args(1), set = bufs)]
     pub bufs: Bufs,
+    #[directive(args(1), set = enum_values)]
+    pub ctx: Enum<propagation::Propagation>,
     #[directive(args(1), set = enum_values, values = propagation::TYPES)]
-    pub ctx: propagation::Type,
-    #[directive(args(1), set = bitmask, values = propagation::TYPES)]
-    pub bitmask: propagation::Type,
+    pub types: usize,
+    #[directive(args(1..=4))]
+    pub bitmask: BitMask<propagation::Propagation>,
 }
 
-mod propagation {
-    use std::mem;
+#[derive(Clone, Debug, Conf)]
+#[conf(default = unset)]
+pub struct Inferred<'a> {
+    #[directive(args(1))]
+    pub flag: conf::Flag,
+    #[directive(args(1))]
+    pub enable: Option<bool>,
+    #[directive(args(1))]
+    pub str: Str,
+    #[directive(args(1))]
+    pub string: Option<String>,
+    #[directive(args(1))]
+    pub str_array: Option<&'a ArrayRef<Str>>,
+    #[directive(args(1))]
+    pub num: isize,
+    #[directive(args(1), set = size)]
+    pub size: usize,
+    #[directive(args(1))]
+    pub msec: MSec,
+    #[directive(args(1))]
+    pub sec: Sec,
+    #[directive(args(1))]
+    pub timeout: Option<Duration>,
+    #[directive(args(2))]
+    pub bufs: Bufs,
+}
+
+#[test]
+fn set_value() {
+    let fixture = Fixture::new();
+    let mut conf = Inferred::default();
+
+    fixture
+        .parse("enable on; string foo; timeout 5s;", &mut conf)
+        .unwrap();
+
+    assert_eq!(conf.enable, Some(true));
+    assert_eq!(conf.string.as_deref(), Some("foo"));
+    assert_eq!(conf.timeout, Some(Duration::from_secs(5)));
+
+    for name in ["enable", "string", "timeout"] {
+        let fixture = Fixture::new();
+        let err = fixture
+            .parse(&format!("{} on;", name), &mut conf)
+            .unwrap_err();
+
+        assert!(
+            err.contains(&format!("\"{}\" directive is duplicate", name)),
+            "{}",
+            err
+        );
+    }
+
+    for (text, msg) in [
+        (
+            "enable yes;",
+            "invalid value \"yes\", it must be \"on\" or \"off\"",
+        ),
+        ("timeout forever;", "invalid value \"forever\""),
+        ("timeout 1x;", "invalid value \"1x\""),
+    ] {
+        let fixture = Fixture::new();
+        let err = fixture.parse(text, &mut Inferred::default()).unwrap_err();
+
+        assert!(err.contains(msg), "{}", err);
+    }
+
+    // `off`, `0s` and an empty string are set values, which are reported as duplicate.
+    let fixture = Fixture::new();
+    let mut conf = Inferred::default();
+
+    fixture
+        .parse("enable off; string \"\"; timeout 0s;", &mut conf)
+        .unwrap();
+
+    assert_eq!(conf.enable, Some(false));
+    assert_eq!(conf.string.as_deref(), Some(""));
+    assert_eq!(conf.timeout, Some(Duration::ZERO));
 
-    use ngx_mod::rt::{core::Unset, ngx_enum_values};
+    for name in ["enable", "string", "timeout"] {
+        let fixture = Fixture::new();
+        let err = fixture
+            .parse(&format!("{} on;", name), &mut conf)
+            .unwrap_err();
+
+        assert!(err.contains("is duplicate"), "{}", err);
+    }
+}
+
+#[derive(Clone, Debug, Conf)]
+#[conf(default = unset)]
+pub struct Parsed {
+    #[directive(args(1), set = parse)]
+    pub addr: Option<SocketAddr>,
+    #[directive(args(1), set = parse)]
+    pub ip: Option<IpAddr>,
+    #[directive(args(1), set = parse)]
+    pub weight: u32,
+    #[cfg(feature = "serde")]
+    #[directive(args(2), set = parse)]
+    pub upstream: Option<(String, u16)>,
+    #[cfg(feature = "serde")]
+    #[directive(args(1..=7), set = deserialize)]
+    pub ports: Vec<u16>,
+}
+
+#[test]
+fn set_parse() {
+    let fixture = Fixture::new();
+    let mut conf = Parsed::default();
+
+    fixture
+        .parse("addr 127.0.0.1:80; weight 3;", &mut conf)
+        .unwrap();
+
+    assert_eq!(conf.addr, Some("127.0.0.1:80".parse().unwrap()));
+    assert_eq!(conf.ip, None);
+    assert_eq!(conf.weight, 3);
+
+    for text in ["addr 127.0.0.1:81;", "weight 4;"] {
+        let fixture = Fixture::new();
+        let err = fixture.parse(text, &mut conf).unwrap_err();
+
+        assert!(err.contains("directive is duplicate"), "{}", err);
+    }
+
+    let err = fixture
+        .parse("ip localhost;", &mut Parsed::default())
+        .unwrap_err();
+
+    assert!(err.contains("invalid value \"localhost\""), "{}", err);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn set_deserialize() {
+    let fixture = Fixture::new();
+    let mut conf = Parsed::default();
+
+    fixture.parse("ports 80 443;", &mut conf).unwrap();
+
+    assert_eq!(conf.ports, [80, 443]);
+
+    let err = fixture.parse("ports 8080;", &mut conf).unwrap_err();
+
+    assert!(err.contains("\"ports\" directive is duplicate"), "{}", err);
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(default = unset)]
+pub struct Multi {
+    #[directive(args(2), multi)]
+    pub headers: BTreeMap<String, String>,
+    #[directive(args(1..=7), multi(append))]
+    pub ports: Vec<u16>,
+}
+
+#[derive(Clone, Debug, Conf, Merge)]
+#[conf(default = unset)]
+pub struct MultiHashMap {
+    #[directive(args(2), multi(replace))]
+    pub params: HashMap<String, usize>,
+}
+
+#[test]
+fn merge_multi() {
+    let prev = Multi {
+        headers: BTreeMap::from([("X-Foo".to_string(), "foo".to_string())]),
+        ports: vec![80],
+    };
+    let mut conf = Multi {
+        ports: vec![443],
+        ..Multi::default()
+    };
+
+    conf.merge(&prev).unwrap();
+
+    assert_eq!(conf.headers, prev.headers);
+    assert_eq!(conf.ports, [80, 443]);
+
+    let prev = MultiHashMap {
+        params: HashMap::from([("a".to_string(), 1)]),
+    };
+    let mut conf = MultiHashMap::default();
+
+    conf.merge(&prev).unwrap();
+
+    assert!(
+        conf.params.is_unset(),
+        "should never inherit the replaced values"
+    );
+}
+
+#[derive(Clone, Debug, Conf)]
+#[conf(default = unset)]
+pub struct Policy {
+    #[directive(args(1))]
+    pub action: Str,
+    #[directive(args(2), multi)]
+    pub rules: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, Conf)]
+pub struct Blocks {
+    #[directive(block)]
+    pub limits: Inferred<'static>,
+    #[directive(block)]
+    pub default_policy: Option<Policy>,
+    #[directive(name = "policy", args(1), block)]
+    pub policies: BTreeMap<String, Policy>,
+    #[directive(name = "named_limits", args(1), block)]
+    pub named_limits: HashMap<String, Parsed>,
+}
+
+#[test]
+fn set_block() {
+    let fixture = Fixture::new();
+    let mut conf = Blocks::default();
+
+    fixture
+        .parse(
+            "limits { enable on; }\n\
+             default_policy { action deny; }\n\
+             policy strict { action deny; }\n\
+             named_limits a { weight 1; }",
+            &mut conf,
+        )
+        .unwrap();
+
+    assert_eq!(conf.limits.enable, Some(true));
+    assert_eq!(
+        conf.default_policy.as_ref().map(|p| p.action.to_string()),
+        Some("deny".to_string())
+    );
+    assert!(conf.policies.contains_key("strict"));
+    assert_eq!(conf.named_limits["a"].weight, 1);
+
+    for (text, msg) in [
+        ("limits { num 1; }", "\"limits\" directive is duplicate"),
+        (
+            "default_policy { action allow; }",
+            "\"default_policy\" directive is duplicate",
+        ),
+        ("policy strict { }", "duplicate block \"strict\""),
+        ("named_limits a { }", "duplicate block \"a\""),
+    ] {
+        let fixture = Fixture::new();
+        let err = fixture.parse(text, &mut conf).unwrap_err();
+
+        assert!(err.contains(msg), "{}", err);
+    }
+}
+
+mod propagation {
+    use ngx_mod::{rt::ngx_enum_values, NgxEnum};
 
     bitflags::bitflags! {
         #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -47,12 +315,13 @@ mod propagation {
         }
     }
 
-    impl Unset for Type {
-        const UNSET: Self = unsafe { mem::transmute(usize::MAX) };
-
-        fn is_unset(&self) -> bool {
-            *self == Self::UNSET
-        }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, NgxEnum)]
+    pub enum Propagation {
+        #[ngx(rename = "ignore")]
+        None = 0,
+        Extract = 0x0001,
+        Inject = 0x0002,
+        Propagate = 0x0003,
     }
 
     ngx_enum_values! {
@@ -64,3 +333,162 @@ mod propagation {
         }
     }
 }
+
+#[test]
+fn ngx_enum() {
+    use ngx_mod::rt::core::conf::NgxEnum;
+    use propagation::{Propagation, Type};
+
+    assert_eq!(
+        Propagation::VALUES
+            .iter()
+            .map(|v| (Str::from(v.name).to_string(), v.value))
+            .collect::<Vec<_>>(),
+        [
+            ("ignore".to_string(), 0),
+            ("extract".to_string(), 1),
+            ("inject".to_string(), 2),
+            ("propagate".to_string(), 3),
+            ("".to_string(), 0),
+        ]
+    );
+    assert_eq!(Propagation::from_value(2), Some(Propagation::Inject));
+    assert_eq!(Propagation::from_value(4), None);
+    assert_eq!(Propagation::Propagate.name(), "propagate");
+
+    let conf = Conf::default();
+
+    assert_eq!(conf.ctx.get(), None);
+    assert_eq!(
+        Enum::from(Propagation::Extract).get(),
+        Some(Propagation::Extract)
+    );
+
+    let mask = [Propagation::Extract, Propagation::Inject]
+        .into_iter()
+        .collect::<BitMask<Propagation>>();
+
+    assert!(conf.bitmask.is_unset());
+    assert!(mask.contains(Propagation::Propagate));
+    assert_eq!(mask.flags::<Type>(), Type::EXTRACT | Type::INJECT);
+
+    let fixture = Fixture::new();
+    let mut conf = Conf::default();
+
+    assert!(conf.types.is_unset());
+
+    fixture
+        .parse("ctx inject; types propagate;", &mut conf)
+        .unwrap();
+
+    assert_eq!(conf.ctx.get(), Some(Propagation::Inject));
+    assert_eq!(
+        Type::from_bits_truncate(conf.types),
+        Type::EXTRACT | Type::INJECT
+    );
+
+    let err = Fixture::new()
+        .parse("types all;", &mut Conf::default())
+        .unwrap_err();
+
+    assert!(err.contains("invalid value \"all\""), "{}", err);
+}
+
+#[derive(Clone, Debug, Conf)]
+#[conf(default = unset)]
+pub struct Validated {
+    #[directive(args(1), range = 1..=65535)]
+    pub port: isize,
+    #[directive(args(1), set = size, min_size = "4k")]
+    pub buffer_size: usize,
+    #[directive(args(1), set = parse, range = 1..=100)]
+    pub weight: Option<u32>,
+    #[directive(args(1), one_of = ["gzip", "br"])]
+    pub compression: Str,
+    #[directive(args(1), deprecated = "use \"timeout\" instead")]
+    pub read_timeout: MSec,
+    #[directive(args(1), conflicts_with = "read_timeout")]
+    pub timeout: Option<Duration>,
+    #[directive(name = "send_timeout", args(1))]
+    pub write_timeout: MSec,
+    #[directive(args(1), conflicts_with = "send_timeout")]
+    pub idle_timeout: MSec,
+    #[directive(args(1), deprecated = "use \"bitmask\" instead")]
+    pub ctx: Enum<propagation::Propagation>,
+    #[directive(args(1..=4), conflicts_with = "ctx")]
+    pub bitmask: BitMask<propagation::Propagation>,
+    #[directive(args(1), set = set_level, deprecated = "use \"port\" instead")]
+    pub level: isize,
+}
+
+#[native_setter]
+fn set_level(cf: &ConfRef, _cmd: &CmdRef, conf: &mut Validated) -> anyhow::Result<()> {
+    conf.level = cf.args()[1].to_str()?.parse()?;
+
+    Ok(())
+}
+
+#[test]
+fn set_validated() {
+    let fixture = Fixture::new();
+    let mut conf = Validated::default();
+
+    fixture
+        .parse(
+            "port 8080; buffer_size 8k; weight 10; compression gzip; read_timeout 5s; \
+             send_timeout 10s; ctx extract; level 1;",
+            &mut conf,
+        )
+        .unwrap();
+
+    assert_eq!(conf.port, 8080);
+    assert_eq!(conf.write_timeout, MSec::from(10000));
+    assert_eq!(conf.ctx.get(), Some(propagation::Propagation::Extract));
+    assert_eq!(conf.level, 1);
+
+    // the setters which don't call the post handler are validated by the wrapper
+    for name in ["read_timeout", "ctx", "level"] {
+        assert!(
+            fixture
+                .log()
+                .contains(&format!("\"{}\" directive is deprecated", name)),
+            "{}",
+            fixture.log()
+        );
+    }
+
+    for (text, msg) in [
+        (
+            "port 0;",
+            "invalid value \"0\", it must be in range 1..=65535",
+        ),
+        ("buffer_size 1k;", "it must be at least 4k"),
+        ("weight 101;", "it must be in range 1..=100"),
+        ("compression zstd;", "it must be one of \"gzip\", \"br\""),
+        (
+            "read_timeout 5s; timeout 5s;",
+            "\"timeout\" directive conflicts with \"read_timeout\"",
+        ),
+        (
+            "send_timeout 5s; idle_timeout 5s;",
+            "\"idle_timeout\" directive conflicts with \"send_timeout\"",
+        ),
+        (
+            "idle_timeout 5s; send_timeout 5s;",
+            "\"send_timeout\" directive conflicts with \"idle_timeout\"",
+        ),
+        (
+            "ctx extract; bitmask inject;",
+            "\"bitmask\" directive conflicts with \"ctx\"",
+        ),
+        (
+            "bitmask inject; ctx extract;",
+            "\"ctx\" directive conflicts with \"bitmask\"",
+        ),
+    ] {
+        let fixture = Fixture::new();
+        let err = fixture.parse(text, &mut Validated::default()).unwrap_err();
+
+        assert!(err.contains(msg), "{}", err);
+    }
+}
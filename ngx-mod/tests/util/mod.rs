@@ -0,0 +1,115 @@
+//! Parses the directives of the tests like a block of `nginx.conf`, without starting nginx.
+
+#![allow(dead_code)]
+
+use std::{
+    env,
+    fs::{self, File},
+    mem::zeroed,
+    os::fd::AsRawFd,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use ngx_mod::rt::{
+    core::{Conf, ConfExt, CycleRef, Log, Pool, Str},
+    ffi,
+};
+
+/// A fake cycle to parse the configuration, which logs the messages to a file.
+pub struct Fixture {
+    pub pool: Pool,
+    cycle: Box<ffi::ngx_cycle_t>,
+    log: Box<ffi::ngx_log_t>,
+    log_file: Box<ffi::ngx_open_file_t>,
+    file: File,
+    path: PathBuf,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        static SEQ: AtomicUsize = AtomicUsize::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "ngx-mod-test-{}-{}.log",
+            process::id(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create(&path).unwrap();
+        let pool = Pool::new(4096, Log::stderr()).unwrap();
+
+        unsafe {
+            let mut log_file: Box<ffi::ngx_open_file_t> = Box::new(zeroed());
+            let mut log: Box<ffi::ngx_log_t> = Box::new(zeroed());
+            let mut cycle: Box<ffi::ngx_cycle_t> = Box::new(zeroed());
+
+            log_file.fd = file.as_raw_fd();
+            log.log_level = ffi::NGX_LOG_INFO as _;
+            log.file = &mut *log_file;
+            cycle.pool = pool.as_ptr();
+            cycle.log = &mut *log;
+
+            Fixture {
+                pool,
+                cycle,
+                log,
+                log_file,
+                file,
+                path,
+            }
+        }
+    }
+
+    pub fn cycle(&self) -> &CycleRef {
+        unsafe { CycleRef::from_ptr(&*self.cycle as *const _ as *mut _) }
+    }
+
+    /// Returns the configuration to parse the directives in `text`, as if they were in a block.
+    pub fn conf(&self, text: &str) -> Conf {
+        let cf = Conf::with_cycle(self.cycle());
+        let text: ffi::ngx_str_t = (&self.pool.strdup(format!("{}\n}}", text)).unwrap()).into();
+        let name: ffi::ngx_str_t = (&self.pool.strdup("test.conf").unwrap()).into();
+
+        unsafe {
+            let b = self.pool.calloc::<ffi::ngx_buf_t>().unwrap();
+
+            b.start = text.data;
+            b.pos = text.data;
+            b.last = text.data.add(text.len);
+            b.end = b.last;
+
+            let conf_file = self.pool.calloc::<ffi::ngx_conf_file_t>().unwrap();
+
+            // the text is already in the buffer, nothing will be read from the file
+            conf_file.file.fd = self.file.as_raw_fd();
+            conf_file.file.name = name;
+            conf_file.buffer = b;
+            conf_file.line = 1;
+
+            let raw = &mut *cf.as_ptr();
+
+            raw.args = ffi::ngx_array_create(self.pool.as_ptr(), 10, std::mem::size_of::<Str>());
+            raw.conf_file = conf_file;
+        }
+
+        cf
+    }
+
+    /// Parses the directives in `text` into `conf`, returns the logged messages if failed.
+    pub fn parse<T: ConfExt>(&self, text: &str, conf: &mut T) -> Result<(), String> {
+        self.conf(text).parse_block(conf).map_err(|_| self.log())
+    }
+
+    /// Returns the messages logged so far.
+    pub fn log(&self) -> String {
+        fs::read_to_string(&self.path).unwrap()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -53,6 +53,13 @@ pub fn expand(args: Args, f: ItemFn, style: Style) -> TokenStream {
     } = sig;
     let (_impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    if let (Style::Setter, Some(log)) = (&style, args.log()) {
+        abort!(
+            log.span(),
+            "the setter reports the error to the configuration, `log` is not supported"
+        )
+    }
+
     let ngx_rt = find_ngx_rt();
     let name = args.name.as_ref().map_or(&ident, |arg| &arg.value);
     let (unsafe_args, unsafe_params) = inputs
@@ -144,22 +151,17 @@ pub fn expand(args: Args, f: ItemFn, style: Style) -> TokenStream {
             ),
             Style::Setter => (
                 if is_result(&output) {
-                    if let Some(log) = args.log() {
-                        parse_quote_spanned! { output.span() =>
-                            match #handler {
-                                Ok(ok) => { #ngx_rt ::RawOk::<*mut ::std::ffi::c_char>::raw_ok(ok) }
-                                Err(err) => {
-                                    #ngx_rt ::core::Logger::emerg (
-                                        #log,
-                                        format!("call `{}` failed, {}", stringify!(#ident), err) );
+                    let Some(cf) = unsafe_params.first() else {
+                        abort!(
+                            inputs.span(),
+                            "the setter should take `cf: &ConfRef` argument"
+                        )
+                    };
 
-                                    #ngx_rt ::RawErr::<*mut ::std::ffi::c_char>::raw_err(())
-                                }
-                            }
-                        }
-                    } else {
-                        parse_quote_spanned! { output.span() =>
-                            #ngx_rt ::RawResult::<*mut ::std::ffi::c_char>::raw_result(#handler)
+                    parse_quote_spanned! { output.span() =>
+                        match #handler {
+                            Ok(ok) => { #ngx_rt ::RawOk::<*mut ::std::ffi::c_char>::raw_ok(ok) }
+                            Err(err) => #ngx_rt ::core::conf::conf_error( #cf, err ),
                         }
                     }
                 } else {
@@ -92,6 +92,23 @@ impl<T: Sized> ArrayRef<T> {
     pub fn push(&mut self, value: T) -> Option<&mut T> {
         self.reserve().map(|p| p.write(value))
     }
+
+    /// Inserts an element at position `idx`, shifting all elements after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx > len`.
+    pub fn insert(&mut self, idx: usize, value: T) -> Option<&mut T> {
+        assert!(idx <= self.len(), "insertion index out of bounds");
+
+        self.push(value)?;
+
+        let s = self.as_mut_slice();
+
+        s[idx..].rotate_right(1);
+
+        Some(&mut s[idx])
+    }
 }
 
 impl<T: Sized> AsRef<ffi::ngx_array_t> for ArrayRef<T> {
@@ -196,5 +213,11 @@ mod tests {
         assert!(a.is_full());
 
         assert_eq!(a.as_slice(), &[1, 2, 3, 4]);
+
+        assert_eq!(a.insert(0, 0).unwrap(), &0);
+        assert_eq!(a.insert(3, 5).unwrap(), &5);
+        assert_eq!(a.insert(6, 6).unwrap(), &6);
+
+        assert_eq!(a.as_slice(), &[0, 1, 2, 5, 3, 4, 6]);
     }
 }
@@ -80,6 +80,34 @@ bitflags! {
 
 impl Type {
     pub const MAX_ARGS: usize = ffi::NGX_CONF_MAX_ARGS as usize;
+
+    const ARGUMENT_NUMBER: [Type; 8] = [
+        Type::NO_ARGS,
+        Type::TAKE1,
+        Type::TAKE2,
+        Type::TAKE3,
+        Type::TAKE4,
+        Type::TAKE5,
+        Type::TAKE6,
+        Type::TAKE7,
+    ];
+
+    /// Returns `true` if the directive takes `n` arguments, excluding the directive name.
+    pub fn accepts_args(&self, n: usize) -> bool {
+        if self.contains(Type::ANY) {
+            true
+        } else if self.contains(Type::FLAG) {
+            n == 1
+        } else if self.contains(Type::ONE_MORE) {
+            n >= 1
+        } else if self.contains(Type::TWO_MORE) {
+            n >= 2
+        } else {
+            Self::ARGUMENT_NUMBER
+                .get(n)
+                .is_some_and(|&ty| self.contains(ty))
+        }
+    }
 }
 
 #[macro_export]
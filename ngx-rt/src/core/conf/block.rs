@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{c_char, c_void, CString};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::str::FromStr;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{ConfExt, ConfRef, Str, Unset},
+    ffi, Error,
+};
+
+use super::{check::call_post, set::conf_error};
+
+/// A field which is set by a block directive.
+///
+/// The block is parsed by [`set_block_slot`] with the commands of the nested configuration,
+/// which is used by `#[derive(Conf)]` for the field with `#[directive(block)]`.
+pub trait ConfBlock {
+    /// Parses the block, `args` are the directive arguments excluding the directive name.
+    fn parse_block(&mut self, cf: &mut ConfRef, args: &[Str]) -> Result<(), Error>;
+
+    /// Sets the default values of the nested configuration if the block is absent.
+    fn init_defaults(&mut self, _cf: &ConfRef) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Parses the anonymous block into the nested configuration, which can be set only once.
+impl<T: ConfExt + Unset> ConfBlock for T {
+    fn parse_block(&mut self, cf: &mut ConfRef, args: &[Str]) -> Result<(), Error> {
+        no_args(args)?;
+
+        if !self.is_unset() {
+            return Err(Error::ConfigError(CString::new("is duplicate")?));
+        }
+
+        cf.parse_block(self)
+    }
+
+    fn init_defaults(&mut self, cf: &ConfRef) -> Result<(), Error> {
+        ConfExt::init_defaults(self, cf)
+    }
+}
+
+/// Parses the anonymous block into the nested configuration, which can be set only once.
+impl<T: ConfExt + Default> ConfBlock for Option<T> {
+    fn parse_block(&mut self, cf: &mut ConfRef, args: &[Str]) -> Result<(), Error> {
+        no_args(args)?;
+
+        if self.is_some() {
+            return Err(Error::ConfigError(CString::new("is duplicate")?));
+        }
+
+        cf.parse_block(self.insert(T::default()))
+    }
+}
+
+/// Parses the named block, e.g. `policy name { ... }`, into the nested configuration of the name.
+impl<K, T, S> ConfBlock for HashMap<K, T, S>
+where
+    K: FromStr + Hash + Eq,
+    K::Err: fmt::Display,
+    T: ConfExt + Default,
+    S: BuildHasher,
+{
+    fn parse_block(&mut self, cf: &mut ConfRef, args: &[Str]) -> Result<(), Error> {
+        let (name, key) = block_name::<K>(args)?;
+
+        if self.contains_key(&key) {
+            return Err(duplicate_block(name));
+        }
+
+        cf.parse_block(self.entry(key).or_default())
+    }
+}
+
+/// Parses the named block, e.g. `policy name { ... }`, into the nested configuration of the name.
+impl<K, T> ConfBlock for BTreeMap<K, T>
+where
+    K: FromStr + Ord,
+    K::Err: fmt::Display,
+    T: ConfExt + Default,
+{
+    fn parse_block(&mut self, cf: &mut ConfRef, args: &[Str]) -> Result<(), Error> {
+        let (name, key) = block_name::<K>(args)?;
+
+        if self.contains_key(&key) {
+            return Err(duplicate_block(name));
+        }
+
+        cf.parse_block(self.entry(key).or_default())
+    }
+}
+
+fn no_args(args: &[Str]) -> Result<(), Error> {
+    if args.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ConfigError(CString::new(
+            "invalid number of arguments",
+        )?))
+    }
+}
+
+fn block_name<K>(args: &[Str]) -> Result<(&Str, K), Error>
+where
+    K: FromStr,
+    K::Err: fmt::Display,
+{
+    match args {
+        [name] => name
+            .to_str()
+            .map_err(|err| err.to_string())
+            .and_then(|s| s.parse::<K>().map_err(|err| err.to_string()))
+            .map(|key| (name, key))
+            .map_err(|err| {
+                Error::ConfigError(
+                    CString::new(format!("invalid name \"{}\", {}", name, err)).unwrap_or_default(),
+                )
+            }),
+        _ => Err(Error::ConfigError(CString::new(
+            "invalid number of arguments",
+        )?)),
+    }
+}
+
+fn duplicate_block(name: &Str) -> Error {
+    Error::ConfigError(CString::new(format!("duplicate block \"{}\"", name)).unwrap_or_default())
+}
+
+/// Parses the block of the directive into the nested configuration field.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to an initialized field of type `F` in the `conf`.
+pub unsafe extern "C" fn set_block_slot<F: ConfBlock>(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char {
+    let field = &mut *conf.cast::<u8>().add((*cmd).offset).cast::<F>();
+    let cf = ConfRef::from_ptr_mut(cf);
+    let args = cf.args().as_slice()[1..].to_vec();
+
+    match field.parse_block(cf, &args) {
+        Ok(_) => call_post(cf.as_ptr(), cmd, (field as *mut F).cast()),
+        Err(err) => conf_error(cf, err),
+    }
+}
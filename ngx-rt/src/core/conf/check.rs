@@ -0,0 +1,142 @@
+use std::ffi::{c_char, c_void, CString};
+use std::fmt;
+use std::ops::RangeBounds;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{core::ConfRef, ffi, Error};
+
+use super::{default::is_setting_default, set::conf_error, Unset, NGX_CONF_OK};
+
+/// Calls the post handler of the command with the field, like the nginx builtin setters.
+pub(crate) unsafe fn call_post(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    field: *mut c_void,
+) -> *mut c_char {
+    let post = (*cmd).post.cast::<ffi::ngx_conf_post_t>();
+
+    match post.as_ref().and_then(|post| post.post_handler) {
+        Some(handler) => handler(cf, post.cast(), field),
+        None => NGX_CONF_OK,
+    }
+}
+
+/// Validates the field after it has been set by the directive.
+///
+/// This is called by the post handler which `#[derive(Conf)]` generates for the field
+/// with the validation attributes, the `check` closure takes the configuration and the field.
+/// The error is reported as `"name" directive <msg> in <file>:<line>`,
+/// and the default value set by [`set_default`](super::set_default) is not validated.
+///
+/// # Safety
+///
+/// The `field` must point to a field of type `F` at `offset` in the configuration of type `C`.
+pub unsafe fn validate<C, F>(
+    cf: *mut ffi::ngx_conf_t,
+    field: *mut c_void,
+    offset: usize,
+    check: impl FnOnce(&ConfRef, &C, &F) -> Result<(), Error>,
+) -> *mut c_char {
+    if is_setting_default() {
+        return NGX_CONF_OK;
+    }
+
+    let cf = ConfRef::from_ptr(cf);
+    let conf = &*field.cast::<u8>().sub(offset).cast::<C>();
+    let value = &*field.cast::<F>();
+
+    match check(cf, conf, value) {
+        Ok(_) => NGX_CONF_OK,
+        Err(err) => conf_error(cf, err),
+    }
+}
+
+/// Checks the value is in the range, e.g. `#[directive(range = 1..=65535)]`.
+pub fn check_range<T, R>(value: &T, range: &R) -> Result<(), Error>
+where
+    T: PartialOrd + fmt::Display,
+    R: RangeBounds<T> + fmt::Debug,
+{
+    if range.contains(value) {
+        Ok(())
+    } else {
+        Err(invalid(value, format!("it must be in range {:?}", range)))
+    }
+}
+
+/// Checks the size is not less than the minimum, e.g. `#[directive(min_size = "4k")]`.
+pub fn check_min_size(value: &usize, min: usize, desc: &str) -> Result<(), Error> {
+    if *value >= min {
+        Ok(())
+    } else {
+        Err(invalid(value, format!("it must be at least {}", desc)))
+    }
+}
+
+/// Checks the value is one of the allowed values, e.g. `#[directive(one_of = ["on", "off"])]`.
+pub fn check_one_of<T: fmt::Display>(value: &T, values: &[&str]) -> Result<(), Error> {
+    let s = value.to_string();
+
+    if values.contains(&s.as_str()) {
+        Ok(())
+    } else {
+        let values = values
+            .iter()
+            .map(|v| format!("\"{}\"", v))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Err(invalid(s, format!("it must be one of {}", values)))
+    }
+}
+
+/// Checks the conflicting directive has not been set, e.g. `#[directive(conflicts_with = "other")]`.
+pub fn check_conflict<T: Unset>(other: &T, name: &str) -> Result<(), Error> {
+    if other.is_unset() {
+        Ok(())
+    } else {
+        Err(Error::ConfigError(
+            CString::new(format!("conflicts with \"{}\"", name)).unwrap_or_default(),
+        ))
+    }
+}
+
+/// Warns the directive is deprecated, e.g. `#[directive(deprecated = "use \"other\" instead")]`.
+pub fn deprecated(cf: &ConfRef, note: &str) {
+    cf.warn(format!("is deprecated, {}", note))
+}
+
+fn invalid<T: fmt::Display>(value: T, reason: String) -> Error {
+    Error::ConfigError(
+        CString::new(format!("invalid value \"{}\", {}", value, reason)).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check() {
+        assert!(check_range(&80u16, &(1..=65535)).is_ok());
+        assert_eq!(
+            check_range(&0u16, &(1..=65535)).unwrap_err().to_string(),
+            "invalid value \"0\", it must be in range 1..=65535"
+        );
+
+        assert!(check_min_size(&8192, 4096, "4k").is_ok());
+        assert!(check_min_size(&1024, 4096, "4k").is_err());
+
+        assert!(check_one_of(&"gzip", &["gzip", "br"]).is_ok());
+        assert_eq!(
+            check_one_of(&"zstd", &["gzip", "br"])
+                .unwrap_err()
+                .to_string(),
+            "invalid value \"zstd\", it must be one of \"gzip\", \"br\""
+        );
+
+        assert!(check_conflict(&None::<String>, "other").is_ok());
+        assert!(check_conflict(&Some("foo"), "other").is_err());
+    }
+}
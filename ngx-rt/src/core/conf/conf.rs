@@ -1,10 +1,11 @@
 use std::ffi::CString;
+use std::mem;
 #[cfg(target_family = "unix")]
 use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 use std::ptr::{null_mut, NonNull};
 
-use foreign_types::{foreign_type, ForeignTypeRef};
+use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
 
 use crate::core::Cmds;
 use crate::AsRawMut;
@@ -14,6 +15,8 @@ use crate::{
     ffi, http, native_setter, AsRawRef, AsResult, Error,
 };
 
+use super::DirectiveDoc;
+
 foreign_type! {
     pub unsafe type Conf: Send {
         type CType = ffi::ngx_conf_t;
@@ -23,6 +26,22 @@ foreign_type! {
     }
 }
 
+impl Conf {
+    /// Creates the configuration of the cycle, e.g. to set the default values in `init_conf`.
+    pub fn with_cycle(cycle: &CycleRef) -> Self {
+        unsafe {
+            let mut cf: ffi::ngx_conf_t = mem::zeroed();
+
+            cf.cycle = cycle.as_ptr();
+            cf.pool = (*cf.cycle).pool;
+            cf.temp_pool = cf.pool;
+            cf.log = (*cf.cycle).log;
+
+            Conf::from_ptr(Box::into_raw(Box::new(cf)))
+        }
+    }
+}
+
 impl ConfRef {
     property! {
         cycle: &CycleRef;
@@ -43,6 +62,30 @@ impl ConfRef {
         }
     }
 
+    #[cfg(feature = "mail")]
+    pub fn as_mail_context(&self) -> Option<&crate::mail::ConfContextRef> {
+        if self.module_type() == ModuleType::Mail {
+            unsafe {
+                NonNull::new(self.as_raw().ctx)
+                    .map(|p| crate::mail::ConfContextRef::from_ptr(p.cast().as_ptr()))
+            }
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn as_stream_context(&self) -> Option<&crate::stream::ConfContextRef> {
+        if self.module_type() == ModuleType::Stream {
+            unsafe {
+                NonNull::new(self.as_raw().ctx)
+                    .map(|p| crate::stream::ConfContextRef::from_ptr(p.cast().as_ptr()))
+            }
+        } else {
+            None
+        }
+    }
+
     pub fn module_type(&self) -> ModuleType {
         ModuleType::from(unsafe { self.as_raw().module_type as u32 })
     }
@@ -54,8 +97,10 @@ impl ConfRef {
             cf.as_raw_mut().handler = Some(parse_block::<T>);
             cf.as_raw_mut().handler_conf = c as *mut _ as *mut _;
 
-            ffi::ngx_conf_parse(cf.as_ptr(), null_mut()).ok()
+            ffi::ngx_conf_parse(cf.as_ptr(), null_mut()).ok()?;
         }
+
+        c.init_defaults(self)
     }
 
     pub fn parse_file<P: AsRef<Path>>(&mut self, filename: P) -> Result<(), Error> {
@@ -66,28 +111,34 @@ impl ConfRef {
     }
 }
 
-#[native_setter(log = cf)]
+#[native_setter]
 unsafe fn parse_block<T>(cf: &ConfRef, _dummy: Option<&CmdRef>, conf: &mut T) -> Result<(), Error>
 where
     T: ConfExt,
 {
-    let name = cf.args().get(0).unwrap();
-
-    if cf.args().len() != 2 {
-        return Err(Error::ConfigError(CString::new(format!(
-            "invalid number of arguments in directive `{}`",
-            name,
-        ))?));
-    }
+    let name = cf.args().first().unwrap();
 
     for cmd in <T as ConfExt>::commands() {
         if cmd.name() != name {
             continue;
         }
 
+        if !cmd.ty().accepts_args(cf.args().len() - 1) {
+            return Err(Error::ConfigError(CString::new(format!(
+                "invalid number of arguments in \"{}\" directive",
+                name,
+            ))?));
+        }
+
         unsafe {
             return if let Some(f) = cmd.as_raw().set {
-                f(cf.as_ptr(), cmd.as_ptr(), conf as *mut _ as *mut _).ok()
+                // nginx reports the error of the block handler without the directive name
+                match f(cf.as_ptr(), cmd.as_ptr(), conf as *mut _ as *mut _).ok() {
+                    Err(Error::ConfigError(msg)) if !msg.is_empty() => Err(Error::ConfigError(
+                        CString::new(format!("\"{}\" directive {}", name, msg.to_string_lossy()))?,
+                    )),
+                    res => res,
+                }
             } else {
                 Err(Error::ConfigError(CString::new(format!(
                     "directive `{}` missing setter",
@@ -98,13 +149,31 @@ where
     }
 
     Err(Error::ConfigError(CString::new(format!(
-        "unknown directive `{}` in `otel_exporter`",
+        "unknown directive \"{}\"",
         name
     ))?))
 }
 
 pub trait ConfExt: UnsafeConf {
     fn commands() -> Cmds<'static>;
+
+    /// Returns the reference documentation of the directives.
+    fn directives() -> &'static [DirectiveDoc] {
+        &[]
+    }
+
+    /// Sets the default values of the unset directives, e.g. `#[directive(default = "5s")]`.
+    fn init_defaults(&mut self, _cf: &ConfRef) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Checks the required directives have been set, e.g. `#[directive(required)]`.
+    ///
+    /// The configuration is checked at its final level,
+    /// e.g. the main level, the server level, or the location level for the location configuration.
+    fn check_required(&self) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 impl ConfExt for () {
@@ -117,10 +186,19 @@ pub trait UnsafeConf {
     type Commands: Copy;
 
     const COMMANDS: Self::Commands;
+
+    type Doc: Copy;
+
+    /// The directives described in JSON, one object per line.
+    const DOC: Self::Doc;
 }
 
 impl UnsafeConf for () {
     type Commands = [ffi::ngx_command_t; 0];
 
     const COMMANDS: Self::Commands = [];
+
+    type Doc = [u8; 0];
+
+    const DOC: Self::Doc = [];
 }
@@ -0,0 +1,304 @@
+use std::ffi::CString;
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::{core::Str, Error};
+
+/// Deserializes a value from the directive arguments, excluding the directive name.
+///
+/// A single argument is deserialized as the value itself,
+/// multiple arguments are deserialized as a sequence, e.g. into a tuple or struct.
+pub fn from_args<T: DeserializeOwned>(args: &[Str]) -> Result<T, Error> {
+    T::deserialize(ArgsDeserializer(args))
+        .map_err(|err| Error::ConfigError(CString::new(err.0).unwrap_or_default()))
+}
+
+#[derive(Debug)]
+struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+struct ArgsDeserializer<'a>(&'a [Str]);
+
+impl<'a> ArgsDeserializer<'a> {
+    fn single(&self) -> Result<ArgDeserializer<'a>, DeError> {
+        match self.0 {
+            [arg] => Ok(ArgDeserializer(arg)),
+            _ => Err(DeError("invalid number of arguments".to_string())),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ( $( $method:ident ),* ) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ArgsDeserializer<'a> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.len() == 1 {
+            self.single()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    forward_to_single! {
+        deserialize_bool,
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64,
+        deserialize_f32, deserialize_f64, deserialize_char,
+        deserialize_str, deserialize_string, deserialize_bytes, deserialize_byte_buf,
+        deserialize_unit, deserialize_identifier
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Args(self.0.iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 map ignored_any
+    }
+}
+
+struct Args<'a>(slice::Iter<'a, Str>);
+
+impl<'de, 'a> SeqAccess<'de> for Args<'a> {
+    type Error = DeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .next()
+            .map(|arg| seed.deserialize(ArgDeserializer(arg)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct ArgDeserializer<'a>(&'a Str);
+
+impl<'a> ArgDeserializer<'a> {
+    fn to_str(&self) -> Result<&'a str, DeError> {
+        self.0
+            .to_str()
+            .map_err(|err| DeError(format!("invalid value \"{}\", {}", self.0, err)))
+    }
+
+    fn parse<T>(&self) -> Result<T, DeError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.to_str()?
+            .parse()
+            .map_err(|err| DeError(format!("invalid value \"{}\", {}", self.0, err)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ( $( $method:ident => $visit:ident ),* ) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ArgDeserializer<'a> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.to_str()?)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let s = self.to_str()?;
+
+        if s.eq_ignore_ascii_case("on") || s.eq_ignore_ascii_case("true") {
+            visitor.visit_bool(true)
+        } else if s.eq_ignore_ascii_case("off") || s.eq_ignore_ascii_case("false") {
+            visitor.visit_bool(false)
+        } else {
+            Err(DeError(format!(
+                "invalid value \"{}\", it must be \"on\" or \"off\"",
+                s
+            )))
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.0.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(IntoDeserializer::<DeError>::into_deserializer(
+            self.to_str()?,
+        ))
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&'static str]) -> Vec<Str> {
+        args.iter().map(|&s| Str::from(s)).collect()
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(from_args::<u16>(&args(&["8080"])).unwrap(), 8080);
+        assert!(from_args::<bool>(&args(&["on"])).unwrap());
+        assert_eq!(
+            from_args::<(String, u16, bool)>(&args(&["localhost", "80", "off"])).unwrap(),
+            ("localhost".to_string(), 80, false)
+        );
+        assert_eq!(
+            from_args::<Vec<u32>>(&args(&["1", "2", "3"])).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            from_args::<Option<String>>(&args(&["foo"])).unwrap(),
+            Some("foo".to_string())
+        );
+
+        let err = from_args::<(String, u16)>(&args(&["localhost", "http"])).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"http\", invalid digit found in string"
+        );
+    }
+}
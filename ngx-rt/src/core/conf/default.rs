@@ -0,0 +1,77 @@
+use std::{
+    cell::Cell,
+    ffi::{c_void, CString},
+};
+
+use foreign_types::ForeignType;
+
+use crate::{
+    core::{Array, ConfRef, Str},
+    ffi, AsRawMut, AsResult, Error,
+};
+
+thread_local! {
+    static SETTING_DEFAULT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns `true` if the default value of a directive is being set by [`set_default`].
+pub(crate) fn is_setting_default() -> bool {
+    SETTING_DEFAULT.with(Cell::get)
+}
+
+/// Sets the default value of the directive with its setter, as if `name value;` was configured.
+///
+/// The value is not validated by the `post` handler of `#[derive(Conf)]`, e.g. a deprecation warning,
+/// since it is not configured by the user. The `post` of the command is kept for the setters
+/// which take it as their data, e.g. the values of `ngx_conf_set_enum_slot`.
+///
+/// This is called by `#[derive(Conf)]` for the unset field with `#[directive(default = ...)]`.
+///
+/// # Safety
+///
+/// The `conf` must point to the configuration which the `cmd` is declared for.
+pub unsafe fn set_default(
+    cf: &ConfRef,
+    cmd: &ffi::ngx_command_t,
+    conf: *mut c_void,
+    value: &str,
+) -> Result<(), Error> {
+    let name = Str::from(cmd.name);
+    let Some(set) = cmd.set else {
+        return Err(Error::ConfigError(CString::new(format!(
+            "directive `{}` missing setter",
+            name
+        ))?));
+    };
+
+    let value = cf.pool().strdup(value).ok_or(Error::OutOfMemory)?;
+    let mut args = Array::<Str>::create(cf.pool(), 2).ok_or(Error::OutOfMemory)?;
+
+    args.push(name).ok_or(Error::OutOfMemory)?;
+    args.push(value).ok_or(Error::OutOfMemory)?;
+
+    let mut cf = cf.to_owned();
+
+    cf.as_raw_mut().args = args.as_ptr();
+
+    let mut cmd = *cmd;
+
+    SETTING_DEFAULT.with(|c| c.set(true));
+
+    let res = set(cf.as_ptr(), &mut cmd, conf);
+
+    SETTING_DEFAULT.with(|c| c.set(false));
+
+    res.ok().map_err(|err| match err {
+        Error::ConfigError(msg) if !msg.is_empty() => Error::ConfigError(
+            CString::new(format!(
+                "invalid default value \"{}\" of \"{}\" directive, {}",
+                value,
+                name,
+                msg.to_string_lossy()
+            ))
+            .unwrap_or_default(),
+        ),
+        err => err,
+    })
+}
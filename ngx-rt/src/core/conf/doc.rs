@@ -0,0 +1,22 @@
+/// The reference documentation of a directive, which is generated by `#[derive(Conf)]`.
+///
+/// The fields follow the directive reference of nginx.org, e.g.
+///
+/// ```text
+/// Syntax:  awssig on | off;
+/// Default: awssig off;
+/// Context: server, location
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirectiveDoc {
+    /// The name of the directive.
+    pub name: &'static str,
+    /// The syntax of the directive, e.g. `awssig on | off;`.
+    pub syntax: &'static str,
+    /// The default value of the directive, e.g. `awssig off;`.
+    pub default: Option<&'static str>,
+    /// The contexts where the directive is allowed, e.g. `server` or `location`.
+    pub contexts: &'static [&'static str],
+    /// The doc comments of the field.
+    pub doc: &'static str,
+}
@@ -1,7 +1,13 @@
+use std::fmt;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
 
+use bitflags::Flags;
+
 use crate::ffi;
 
+use super::Unset;
+
 #[macro_export]
 macro_rules! ngx_enum_values {
     (
@@ -35,3 +41,162 @@ pub const fn values<const N: usize>(
 ) -> NonNull<ffi::ngx_conf_enum_t> {
     unsafe { NonNull::new_unchecked(values.as_slice().as_ptr() as *mut _) }
 }
+
+/// The Rust enum of the directive values, which is implemented by `#[derive(NgxEnum)]`.
+pub trait NgxEnum: Copy + Sized {
+    /// The names and values of the variants, terminated by an empty name.
+    const VALUES: &'static [ffi::ngx_conf_enum_t];
+
+    /// Returns the variant of the value.
+    fn from_value(value: ffi::ngx_uint_t) -> Option<Self>;
+
+    /// Returns the value of the variant.
+    fn value(self) -> ffi::ngx_uint_t;
+
+    /// Returns the name of the variant in the directive.
+    fn name(self) -> &'static str;
+}
+
+/// The value of the directive which is set with `set = enum_values`.
+#[repr(transparent)]
+pub struct Enum<T>(ffi::ngx_uint_t, PhantomData<T>);
+
+impl<T: NgxEnum> Enum<T> {
+    /// Returns the variant, or `None` if the directive is not set.
+    pub fn get(&self) -> Option<T> {
+        if self.is_unset() {
+            None
+        } else {
+            T::from_value(self.0)
+        }
+    }
+}
+
+impl<T: NgxEnum> From<T> for Enum<T> {
+    fn from(value: T) -> Self {
+        Enum(value.value(), PhantomData)
+    }
+}
+
+impl<T> Unset for Enum<T> {
+    const UNSET: Self = Enum(ffi::ngx_uint_t::MAX, PhantomData);
+
+    fn is_unset(&self) -> bool {
+        self.0 == ffi::ngx_uint_t::MAX
+    }
+}
+
+impl<T> Default for Enum<T> {
+    fn default() -> Self {
+        Self::UNSET
+    }
+}
+
+impl<T> Clone for Enum<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Enum<T> {}
+
+impl<T> PartialEq for Enum<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for Enum<T> {}
+
+impl<T: NgxEnum + fmt::Debug> fmt::Debug for Enum<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Enum").field(&self.get()).finish()
+    }
+}
+
+/// The values of the directive which is set with `set = bitmask`.
+///
+/// The values of the variants are the bits, which could be converted to the `bitflags` type.
+#[repr(transparent)]
+pub struct BitMask<T>(ffi::ngx_uint_t, PhantomData<T>);
+
+impl<T: NgxEnum> BitMask<T> {
+    /// Returns the bits of the values.
+    pub fn bits(&self) -> ffi::ngx_uint_t {
+        self.0
+    }
+
+    /// Returns `true` if the value has been set.
+    pub fn contains(&self, value: T) -> bool {
+        self.0 & value.value() == value.value()
+    }
+
+    /// Sets the value.
+    pub fn insert(&mut self, value: T) {
+        self.0 |= value.value()
+    }
+
+    /// Returns the values as the `bitflags` type.
+    pub fn flags<F>(&self) -> F
+    where
+        F: Flags<Bits = ffi::ngx_uint_t>,
+    {
+        F::from_bits_truncate(self.0)
+    }
+}
+
+impl<T: NgxEnum> FromIterator<T> for BitMask<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut mask = Self::UNSET;
+
+        for value in iter {
+            mask.insert(value);
+        }
+
+        mask
+    }
+}
+
+/// The bitmask is unset without any bits, like `ngx_conf_merge_bitmask_value`.
+impl<T> Unset for BitMask<T> {
+    const UNSET: Self = BitMask(0, PhantomData);
+
+    fn is_unset(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<T> Default for BitMask<T> {
+    fn default() -> Self {
+        Self::UNSET
+    }
+}
+
+impl<T> Clone for BitMask<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BitMask<T> {}
+
+impl<T> PartialEq for BitMask<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for BitMask<T> {}
+
+impl<T: NgxEnum + fmt::Debug> fmt::Debug for BitMask<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(
+                T::VALUES
+                    .iter()
+                    .flat_map(|v| T::from_value(v.value))
+                    .filter(|v| v.value() != 0 && self.contains(*v)),
+            )
+            .finish()
+    }
+}
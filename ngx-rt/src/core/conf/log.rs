@@ -1,4 +1,5 @@
 use std::ffi::CString;
+use std::fmt;
 
 use foreign_types::ForeignTypeRef;
 
@@ -12,6 +13,39 @@ impl ConfRef {
         log: &LogRef;
     }
 
+    /// Returns the name of the directive being parsed, e.g. `listen`.
+    pub fn directive(&self) -> String {
+        self.args()
+            .first()
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Logs an emergency message of the directive being parsed,
+    /// e.g. `nginx: [emerg] "listen" directive <msg>: 80 ssl in <file>:<line>`.
+    pub fn emerg<S: fmt::Display>(&self, msg: S) {
+        self.log_directive(LogLevel::Emerg, msg)
+    }
+
+    /// Logs a warning message of the directive being parsed,
+    /// e.g. `nginx: [warn] "listen" directive <msg>: 80 ssl in <file>:<line>`.
+    pub fn warn<S: fmt::Display>(&self, msg: S) {
+        self.log_directive(LogLevel::Warn, msg)
+    }
+
+    fn log_directive<S: fmt::Display>(&self, level: LogLevel, msg: S) {
+        let args = self.args().iter().skip(1).map(|arg| arg.to_string());
+        let args = args.collect::<Vec<_>>().join(" ");
+
+        let msg = if args.is_empty() {
+            format!("\"{}\" directive {}", self.directive(), msg)
+        } else {
+            format!("\"{}\" directive {}: {}", self.directive(), msg, args)
+        };
+
+        self.log_error(level, None, msg)
+    }
+
     pub fn log_error<S: Into<Vec<u8>>>(&self, level: LogLevel, err: Option<i32>, msg: S) {
         let msg = CString::new(msg).expect("msg");
 
@@ -2,19 +2,39 @@ use std::{ffi::c_char, ptr};
 
 use crate::ffi;
 
+mod block;
+mod check;
 #[allow(clippy::module_inception)]
 mod conf;
+#[cfg(feature = "serde")]
+mod de;
+mod default;
+mod doc;
 #[macro_use]
 mod r#enum;
 mod file;
 mod log;
+mod multi;
 mod open_file;
+mod set;
 mod unset;
 
+pub use self::block::{set_block_slot, ConfBlock};
+pub use self::check::{
+    check_conflict, check_min_size, check_one_of, check_range, deprecated, validate,
+};
 pub use self::conf::{Conf, ConfExt, ConfRef, UnsafeConf};
+#[cfg(feature = "serde")]
+pub use self::de::from_args;
+pub use self::default::set_default;
+pub use self::doc::DirectiveDoc;
 pub use self::file::{ConfFile, ConfFileRef};
+pub use self::multi::{set_multi_slot, MultiMerge, MultiValue};
 pub use self::open_file::{OpenFile, OpenFileRef};
-pub use self::r#enum::values as enum_values;
+pub use self::r#enum::{values as enum_values, BitMask, Enum, NgxEnum};
+#[cfg(feature = "serde")]
+pub use self::set::set_deserialize_slot;
+pub use self::set::{conf_error, set_parse_slot, set_str_array_slot, set_value_slot, ConfValue};
 pub use self::unset::{unset, Unset};
 
 pub const NGX_CONF_OK: *mut c_char = ptr::null_mut();
@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{c_char, c_void, CString};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::str::FromStr;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{ConfRef, Str},
+    ffi, Error,
+};
+
+use super::{check::call_post, set::conf_error};
+
+/// How the values of a repeatable directive are merged with the previous level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiMerge {
+    /// Inherits the previous values if none is set at this level, like `add_header`.
+    #[default]
+    Inherit,
+
+    /// Appends the values at this level to the previous values.
+    Append,
+
+    /// Never inherits the previous values.
+    Replace,
+}
+
+/// A collection which accumulates the values of a repeatable directive.
+///
+/// Each occurrence of the directive is added by [`set_multi_slot`],
+/// which is used by `#[derive(Conf)]` for the field with `#[directive(multi)]`.
+pub trait MultiValue {
+    /// Adds the values parsed from the directive arguments, excluding the directive name.
+    fn push_args(&mut self, args: &[Str]) -> Result<(), Error>;
+
+    /// Merges the values with the previous level.
+    fn merge_multi(&mut self, prev: &Self, merge: MultiMerge);
+}
+
+/// Appends each argument of the directive.
+impl<T> MultiValue for Vec<T>
+where
+    T: FromStr + Clone,
+    T::Err: fmt::Display,
+{
+    fn push_args(&mut self, args: &[Str]) -> Result<(), Error> {
+        for arg in args {
+            self.push(parse(arg)?);
+        }
+
+        Ok(())
+    }
+
+    fn merge_multi(&mut self, prev: &Self, merge: MultiMerge) {
+        match merge {
+            MultiMerge::Inherit if self.is_empty() => self.extend_from_slice(prev),
+            MultiMerge::Append => {
+                self.splice(0..0, prev.iter().cloned());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Inserts the `key value` arguments of the directive.
+impl<K, V, S> MultiValue for HashMap<K, V, S>
+where
+    K: FromStr + Hash + Eq + Clone,
+    K::Err: fmt::Display,
+    V: FromStr + Clone,
+    V::Err: fmt::Display,
+    S: BuildHasher,
+{
+    fn push_args(&mut self, args: &[Str]) -> Result<(), Error> {
+        let (key, value) = key_value(args)?;
+
+        if self.contains_key(&key) {
+            return Err(duplicate_key(&args[0]));
+        }
+
+        self.insert(key, value);
+
+        Ok(())
+    }
+
+    fn merge_multi(&mut self, prev: &Self, merge: MultiMerge) {
+        match merge {
+            MultiMerge::Inherit if self.is_empty() => {
+                self.extend(prev.iter().map(|(k, v)| (k.clone(), v.clone())))
+            }
+            MultiMerge::Append => {
+                for (k, v) in prev {
+                    self.entry(k.clone()).or_insert_with(|| v.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Inserts the `key value` arguments of the directive.
+impl<K, V> MultiValue for BTreeMap<K, V>
+where
+    K: FromStr + Ord + Clone,
+    K::Err: fmt::Display,
+    V: FromStr + Clone,
+    V::Err: fmt::Display,
+{
+    fn push_args(&mut self, args: &[Str]) -> Result<(), Error> {
+        let (key, value) = key_value(args)?;
+
+        if self.contains_key(&key) {
+            return Err(duplicate_key(&args[0]));
+        }
+
+        self.insert(key, value);
+
+        Ok(())
+    }
+
+    fn merge_multi(&mut self, prev: &Self, merge: MultiMerge) {
+        match merge {
+            MultiMerge::Inherit if self.is_empty() => {
+                self.extend(prev.iter().map(|(k, v)| (k.clone(), v.clone())))
+            }
+            MultiMerge::Append => {
+                for (k, v) in prev {
+                    self.entry(k.clone()).or_insert_with(|| v.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse<T>(s: &Str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    s.to_str()
+        .map_err(|err| err.to_string())
+        .and_then(|v| v.parse::<T>().map_err(|err| err.to_string()))
+        .map_err(|err| {
+            Error::ConfigError(
+                CString::new(format!("invalid value \"{}\", {}", s, err)).unwrap_or_default(),
+            )
+        })
+}
+
+fn key_value<K, V>(args: &[Str]) -> Result<(K, V), Error>
+where
+    K: FromStr,
+    K::Err: fmt::Display,
+    V: FromStr,
+    V::Err: fmt::Display,
+{
+    match args {
+        [key, value] => Ok((parse(key)?, parse(value)?)),
+        _ => Err(Error::ConfigError(CString::new(
+            "invalid number of arguments",
+        )?)),
+    }
+}
+
+fn duplicate_key(key: &Str) -> Error {
+    Error::ConfigError(CString::new(format!("duplicate key \"{}\"", key)).unwrap_or_default())
+}
+
+/// Adds the values of each occurrence of a repeatable directive to the collection field.
+///
+/// The collection is dropped with the configuration which is allocated from the config pool.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to an initialized field of type `F` in the `conf`.
+pub unsafe extern "C" fn set_multi_slot<F: MultiValue>(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char {
+    let field = &mut *conf.cast::<u8>().add((*cmd).offset).cast::<F>();
+    let cf = ConfRef::from_ptr(cf);
+    let args = cf.args();
+
+    match field.push_args(&args[1..]) {
+        Ok(_) => call_post(cf.as_ptr(), cmd, (field as *mut F).cast()),
+        Err(err) => conf_error(cf, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_value() {
+        let mut v = Vec::<u16>::new();
+
+        v.push_args(&[Str::from("80"), Str::from("443")]).unwrap();
+        v.push_args(&[Str::from("8080")]).unwrap();
+        assert_eq!(v, [80, 443, 8080]);
+        assert!(v.push_args(&[Str::from("http")]).is_err());
+
+        let mut m = BTreeMap::<String, String>::new();
+
+        m.push_args(&[Str::from("X-Foo"), Str::from("foo")])
+            .unwrap();
+        assert!(m
+            .push_args(&[Str::from("X-Foo"), Str::from("bar")])
+            .is_err());
+        assert!(m.push_args(&[Str::from("X-Bar")]).is_err());
+    }
+
+    #[test]
+    fn merge_multi() {
+        let prev = vec![1, 2];
+
+        let mut v = vec![];
+        v.merge_multi(&prev, MultiMerge::Inherit);
+        assert_eq!(v, [1, 2]);
+
+        let mut v = vec![3];
+        v.merge_multi(&prev, MultiMerge::Inherit);
+        assert_eq!(v, [3]);
+
+        let mut v = vec![3];
+        v.merge_multi(&prev, MultiMerge::Append);
+        assert_eq!(v, [1, 2, 3]);
+
+        let mut v = vec![];
+        v.merge_multi(&prev, MultiMerge::Replace);
+        assert!(v.is_empty());
+
+        let prev = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        let mut m = HashMap::from([("a".to_string(), 3)]);
+        m.merge_multi(&prev, MultiMerge::Append);
+        assert_eq!(
+            m,
+            HashMap::from([("a".to_string(), 3), ("b".to_string(), 2)])
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::ffi::{c_char, c_void, CString};
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{parse_time, ConfRef, Str, Unset},
+    ffi, Error,
+};
+
+use super::{check::call_post, NGX_CONF_ERROR};
+
+const NGX_CONF_UNSET_PTR: *mut ffi::ngx_array_t = usize::MAX as *mut _;
+
+/// An owned Rust value which can be set by a directive.
+///
+/// The directive arguments are parsed by [`set_value_slot`],
+/// which is inferred by `#[derive(Conf)]` for the `Option<T>` field of the implemented type.
+/// The directive is duplicate if the field is not [`Unset`] when it is set.
+pub trait ConfValue: Sized {
+    /// Parses the value from the directive arguments, excluding the directive name.
+    fn from_args(args: &[Str]) -> Result<Self, Error>;
+}
+
+impl ConfValue for bool {
+    fn from_args(args: &[Str]) -> Result<Self, Error> {
+        let s = single_arg(args)?;
+
+        if s.as_bytes().eq_ignore_ascii_case(b"on") {
+            Ok(true)
+        } else if s.as_bytes().eq_ignore_ascii_case(b"off") {
+            Ok(false)
+        } else {
+            Err(invalid_value(
+                s,
+                Some("it must be \"on\" or \"off\"".to_string()),
+            ))
+        }
+    }
+}
+
+impl ConfValue for String {
+    fn from_args(args: &[Str]) -> Result<Self, Error> {
+        let s = single_arg(args)?;
+
+        s.to_str()
+            .map(ToString::to_string)
+            .map_err(|err| invalid_value(s, Some(err.to_string())))
+    }
+}
+
+impl ConfValue for Duration {
+    fn from_args(args: &[Str]) -> Result<Self, Error> {
+        let s = single_arg(args)?;
+
+        s.to_str()
+            .ok()
+            .and_then(parse_time)
+            .ok_or_else(|| invalid_value(s, None))
+    }
+}
+
+impl<T: ConfValue> ConfValue for Option<T> {
+    fn from_args(args: &[Str]) -> Result<Self, Error> {
+        T::from_args(args).map(Some)
+    }
+}
+
+fn single_arg(args: &[Str]) -> Result<&Str, Error> {
+    match args {
+        [s] => Ok(s),
+        _ => Err(Error::ConfigError(CString::new(
+            "invalid number of arguments",
+        )?)),
+    }
+}
+
+fn invalid_value(s: &Str, reason: Option<String>) -> Error {
+    let msg = match reason {
+        Some(reason) => format!("invalid value \"{}\", {}", s, reason),
+        None => format!("invalid value \"{}\"", s),
+    };
+
+    Error::ConfigError(CString::new(msg).unwrap_or_default())
+}
+
+/// Sets the field of an owned Rust type from the directive arguments.
+///
+/// The field must be initialized and unset, otherwise the directive is duplicate,
+/// and the value is dropped with the configuration which is allocated from the config pool.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to a field of type `T` in the `conf`.
+pub unsafe extern "C" fn set_value_slot<T: ConfValue + Unset>(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char {
+    let field = &mut *conf.cast::<u8>().add((*cmd).offset).cast::<T>();
+
+    if !field.is_unset() {
+        return c"is duplicate".as_ptr() as *mut c_char;
+    }
+
+    let cf = ConfRef::from_ptr(cf);
+    let args = cf.args();
+
+    match T::from_args(&args[1..]) {
+        Ok(value) => {
+            *field = value;
+
+            call_post(cf.as_ptr(), cmd, (field as *mut T).cast())
+        }
+        Err(err) => conf_error(cf, err),
+    }
+}
+
+/// Sets the field by parsing the directive argument with [`FromStr`].
+///
+/// The parsed value of type `T` is converted into the field of type `F`,
+/// e.g. `T` for the field of `Option<T>`. The parser's error is reported at the config file and line.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to an initialized field of type `F` in the `conf`,
+/// which is unset, otherwise the directive is duplicate.
+pub unsafe extern "C" fn set_parse_slot<F, T>(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char
+where
+    F: From<T> + Unset,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let field = &mut *conf.cast::<u8>().add((*cmd).offset).cast::<F>();
+
+    if !field.is_unset() {
+        return c"is duplicate".as_ptr() as *mut c_char;
+    }
+
+    let cf = ConfRef::from_ptr(cf);
+    let args = cf.args();
+
+    let res = single_arg(&args[1..]).and_then(|s| {
+        s.to_str()
+            .map_err(|err| invalid_value(s, Some(err.to_string())))?
+            .parse::<T>()
+            .map_err(|err| invalid_value(s, Some(err.to_string())))
+    });
+
+    match res {
+        Ok(value) => {
+            *field = value.into();
+
+            call_post(cf.as_ptr(), cmd, (field as *mut F).cast())
+        }
+        Err(err) => conf_error(cf, err),
+    }
+}
+
+/// Sets the field by deserializing the directive arguments, e.g. into a tuple or struct.
+///
+/// Each argument is deserialized as a sequence element,
+/// the value of type `T` is converted into the field of type `F`.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to an initialized field of type `F` in the `conf`,
+/// which is unset, otherwise the directive is duplicate.
+#[cfg(feature = "serde")]
+pub unsafe extern "C" fn set_deserialize_slot<F, T>(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char
+where
+    F: From<T> + Unset,
+    T: serde::de::DeserializeOwned,
+{
+    let field = &mut *conf.cast::<u8>().add((*cmd).offset).cast::<F>();
+
+    if !field.is_unset() {
+        return c"is duplicate".as_ptr() as *mut c_char;
+    }
+
+    let cf = ConfRef::from_ptr(cf);
+    let args = cf.args();
+
+    match super::de::from_args::<T>(&args[1..]) {
+        Ok(value) => {
+            *field = value.into();
+
+            call_post(cf.as_ptr(), cmd, (field as *mut F).cast())
+        }
+        Err(err) => conf_error(cf, err),
+    }
+}
+
+/// Sets an array of strings like `ngx_conf_set_str_array_slot`,
+/// but also accepts a null array, e.g. the `None` of `Option<&ArrayRef<Str>>`.
+///
+/// # Safety
+///
+/// The `offset` of the command must point to a `*mut ngx_array_t` field in the `conf`.
+pub unsafe extern "C" fn set_str_array_slot(
+    cf: *mut ffi::ngx_conf_t,
+    cmd: *mut ffi::ngx_command_t,
+    conf: *mut c_void,
+) -> *mut c_char {
+    let field = conf
+        .cast::<u8>()
+        .add((*cmd).offset)
+        .cast::<*mut ffi::ngx_array_t>();
+
+    if field.read().is_null() {
+        field.write(NGX_CONF_UNSET_PTR);
+    }
+
+    ffi::ngx_conf_set_str_array_slot(cf, cmd, conf)
+}
+
+/// Returns the error of the directive setter, e.g. from a `#[native_setter]` function.
+///
+/// The message is copied to the config pool, so nginx reports it like the `ngx_conf_set_*` setters
+/// as `"name" directive <msg> in <file>:<line>`. An empty message means the error has been reported.
+pub fn conf_error<E: fmt::Display>(cf: &ConfRef, err: E) -> *mut c_char {
+    let msg = err.to_string();
+
+    if msg.is_empty() {
+        return NGX_CONF_ERROR;
+    }
+
+    let Ok(msg) = CString::new(msg) else {
+        return NGX_CONF_ERROR;
+    };
+    let b = msg.as_bytes_with_nul();
+
+    unsafe {
+        let p = cf.pool().pnalloc(b.len()).cast::<u8>();
+
+        if p.is_null() {
+            return NGX_CONF_ERROR;
+        }
+
+        ptr::copy_nonoverlapping(b.as_ptr(), p, b.len());
+
+        p.cast()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conf_value() {
+        assert!(bool::from_args(&[Str::from("on")]).unwrap());
+        assert!(!bool::from_args(&[Str::from("OFF")]).unwrap());
+        assert!(bool::from_args(&[Str::from("yes")]).is_err());
+        assert!(bool::from_args(&[]).is_err());
+
+        assert_eq!(
+            String::from_args(&[Str::from("foo")]).unwrap(),
+            "foo".to_string()
+        );
+        assert_eq!(
+            Option::<String>::from_args(&[Str::from("foo")]).unwrap(),
+            Some("foo".to_string())
+        );
+        assert!(Option::<String>::from_args(&[Str::from("a"), Str::from("b")]).is_err());
+
+        assert!(!Some(true).is_unset());
+        assert!(None::<bool>.is_unset());
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, HashMap};
+
 use const_zero::const_zero;
 
 use crate::{core::Str, ffi};
@@ -9,6 +11,11 @@ pub const fn unset<T: Unset>() -> T {
 pub trait Unset: Sized {
     const UNSET: Self;
 
+    /// Returns the unset value, override it for types without a `const` unset value.
+    fn unset() -> Self {
+        Self::UNSET
+    }
+
     fn is_unset(&self) -> bool;
 
     fn or_insert(&mut self, value: Self) -> &mut Self {
@@ -133,6 +140,34 @@ impl<T> Unset for Option<T> {
     }
 }
 
+impl<T> Unset for Vec<T> {
+    const UNSET: Self = Vec::new();
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<K, V> Unset for BTreeMap<K, V> {
+    const UNSET: Self = BTreeMap::new();
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<K, V, S: Default> Unset for HashMap<K, V, S> {
+    const UNSET: Self = panic!("`HashMap` has no const unset value, use `Unset::unset()`");
+
+    fn unset() -> Self {
+        HashMap::default()
+    }
+
+    fn is_unset(&self) -> bool {
+        self.is_empty()
+    }
+}
+
 impl Unset for Str {
     const UNSET: Self = Self::null();
 
@@ -7,9 +7,9 @@ use std::{mem, slice};
 use foreign_types::{foreign_type, ForeignTypeRef};
 use num_enum::FromPrimitive;
 
-use crate::{ffi, flag, never_drop, property, AsRawRef, AsResult, Error};
+use crate::{event::EventRef, ffi, flag, never_drop, property, AsRawRef, AsResult, Error};
 
-use super::{BufRef, LogRef, PoolRef};
+use super::{BufRef, Code, LogRef, PoolRef};
 
 foreign_type! {
     pub unsafe type Listening: Send {
@@ -90,6 +90,12 @@ foreign_type! {
 
 impl ConnRef {
     property! {
+        /// The read event of the connection.
+        read: &mut EventRef;
+
+        /// The write event of the connection.
+        write: &mut EventRef;
+
         listening: &ListeningRef;
         sent: i64;
         log: &LogRef;
@@ -156,6 +162,37 @@ impl ConnRef {
         unsafe { ffi::ngx_close_connection(self.as_ptr()) }
     }
 
+    /// Receives data from the connection into `buf`.
+    ///
+    /// Returns `Ok(0)` if the peer has closed the connection,
+    /// or `Err(Code::AGAIN)` if there is no data available yet.
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize, Code> {
+        let n = unsafe {
+            (self.as_raw().recv.expect("recv"))(self.as_ptr(), buf.as_mut_ptr(), buf.len())
+        };
+
+        if n >= 0 {
+            Ok(n as usize)
+        } else {
+            Err(Code::from(n))
+        }
+    }
+
+    /// Sends data from `buf` to the connection.
+    ///
+    /// Returns the number of bytes sent, or `Err(Code::AGAIN)` if the socket is not ready.
+    pub fn send(&self, buf: &[u8]) -> Result<usize, Code> {
+        let n = unsafe {
+            (self.as_raw().send.expect("send"))(self.as_ptr(), buf.as_ptr() as *mut _, buf.len())
+        };
+
+        if n >= 0 {
+            Ok(n as usize)
+        } else {
+            Err(Code::from(n))
+        }
+    }
+
     pub fn set_tcp_nodelay(&self) -> Result<(), Error> {
         unsafe {
             ffi::ngx_tcp_nodelay(self.as_ptr())
@@ -182,7 +219,7 @@ impl AsRawFd for ConnRef {
     }
 }
 
-unsafe fn sockaddr(sa: NonNull<ffi::sockaddr>, len: usize) -> Option<SocketAddr> {
+pub(crate) unsafe fn sockaddr(sa: NonNull<ffi::sockaddr>, len: usize) -> Option<SocketAddr> {
     match sa.as_ref().sa_family as i32 {
         libc::AF_INET if len >= mem::size_of::<libc::sockaddr_in>() => sa
             .as_ptr()
@@ -212,6 +249,41 @@ unsafe fn sockaddr(sa: NonNull<ffi::sockaddr>, len: usize) -> Option<SocketAddr>
     }
 }
 
+/// Allocates a `sockaddr` for the address from the pool.
+pub(crate) fn alloc_sockaddr(
+    pool: &PoolRef,
+    addr: SocketAddr,
+) -> Option<(*mut ffi::sockaddr, usize)> {
+    Some(match addr {
+        SocketAddr::V4(addr) => {
+            let sin = pool.calloc::<libc::sockaddr_in>()?;
+
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = addr.port().to_be();
+            sin.sin_addr.s_addr = u32::from_ne_bytes(addr.ip().octets());
+
+            (
+                sin as *mut _ as *mut ffi::sockaddr,
+                mem::size_of::<libc::sockaddr_in>(),
+            )
+        }
+        SocketAddr::V6(addr) => {
+            let sin6 = pool.calloc::<libc::sockaddr_in6>()?;
+
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = addr.port().to_be();
+            sin6.sin6_addr.s6_addr = addr.ip().octets();
+            sin6.sin6_flowinfo = addr.flowinfo();
+            sin6.sin6_scope_id = addr.scope_id();
+
+            (
+                sin6 as *mut _ as *mut ffi::sockaddr,
+                mem::size_of::<libc::sockaddr_in6>(),
+            )
+        }
+    })
+}
+
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SocketType(i32);
@@ -15,19 +15,23 @@ mod kv;
 mod module;
 mod parse;
 mod pool;
+pub mod queue;
 pub mod rbtree;
 mod shm;
 mod size;
 mod status;
 mod str;
 pub mod time;
+mod value;
 
 pub use self::array::{Array, ArrayRef};
 pub use self::buf::{Buf, BufRef, Bufs};
 pub use self::cmd::{Cmd, CmdIter, CmdRef, Cmds};
 pub use self::conf::{
-    Conf, ConfExt, ConfFile, ConfFileRef, ConfRef, UnsafeConf, Unset, NGX_CONF_ERROR, NGX_CONF_OK,
+    Conf, ConfExt, ConfFile, ConfFileRef, ConfRef, DirectiveDoc, UnsafeConf, Unset, NGX_CONF_ERROR,
+    NGX_CONF_OK,
 };
+pub(crate) use self::conn::{alloc_sockaddr, sockaddr};
 pub use self::conn::{
     Conn, ConnList, ConnRef, ConnSlice, ConnsIter, LogError, SocketType, TcpNoDelay, TcpNoPush,
 };
@@ -43,8 +47,10 @@ pub use self::log::{Level as LogLevel, Log, LogRef, Logger};
 pub use self::module::{Module, ModuleRef, Type as ModuleType};
 pub use self::parse::{parse_offset, parse_size, parse_time};
 pub use self::pool::{Cleanup, CleanupFn, CleanupRef, Pool, PoolRef};
+pub use self::queue::{Queue, QueueRef};
 pub use self::shm::{Shm, ShmRef, Zone, ZoneRef};
 pub use self::size::SizeFmt;
 pub use self::status::Code;
 pub use self::str::Str;
 pub use self::time::{MSec, Sec};
+pub use self::value::{Value, ValueRef};
@@ -190,7 +190,7 @@ impl PoolRef {
 ///
 /// * `data` - A raw pointer to the value of type `T` to be cleaned up.
 unsafe extern "C" fn cleanup_type<T>(data: *mut c_void) {
-    if data.is_null() {
+    if !data.is_null() {
         ptr::drop_in_place(data.cast::<T>());
     }
 }
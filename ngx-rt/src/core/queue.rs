@@ -0,0 +1,177 @@
+use std::ptr::{self, NonNull};
+
+use foreign_types::{foreign_type, ForeignTypeRef};
+
+use crate::{ffi, never_drop, AsRawMut, AsRawRef};
+
+foreign_type! {
+    /// An intrusive doubly linked list.
+    ///
+    /// The list head and its elements are all `ngx_queue_t`,
+    /// the element is usually embedded in the structure it links.
+    pub unsafe type Queue: Send {
+        type CType = ffi::ngx_queue_t;
+
+        fn drop = never_drop::<ffi::ngx_queue_t>;
+    }
+}
+
+impl QueueRef {
+    /// Initializes an empty list head.
+    pub fn init(&mut self) -> &mut Self {
+        unsafe {
+            let p = self.as_ptr();
+            let q = self.as_raw_mut();
+
+            q.prev = p;
+            q.next = p;
+        }
+
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { ptr::eq(self.as_raw().prev, self.as_ptr()) }
+    }
+
+    /// Returns the first element of the list.
+    pub fn head(&self) -> Option<&QueueRef> {
+        self.element(unsafe { self.as_raw().next })
+    }
+
+    /// Returns the last element of the list.
+    pub fn last(&self) -> Option<&QueueRef> {
+        self.element(unsafe { self.as_raw().prev })
+    }
+
+    /// Inserts the element `x` at the beginning of the list.
+    pub fn insert_head(&mut self, x: &mut QueueRef) {
+        unsafe {
+            let h = self.as_raw_mut();
+            let x = x.as_raw_mut();
+
+            x.next = h.next;
+            (*x.next).prev = x;
+            x.prev = h;
+            h.next = x;
+        }
+    }
+
+    /// Inserts the element `x` at the end of the list.
+    pub fn insert_tail(&mut self, x: &mut QueueRef) {
+        unsafe {
+            let h = self.as_raw_mut();
+            let x = x.as_raw_mut();
+
+            x.prev = h.prev;
+            (*x.prev).next = x;
+            x.next = h;
+            h.prev = x;
+        }
+    }
+
+    /// Removes the element from the list it is linked to.
+    pub fn remove(&mut self) {
+        unsafe {
+            let x = self.as_raw_mut();
+
+            (*x.next).prev = x.prev;
+            (*x.prev).next = x.next;
+
+            if cfg!(debug_assertions) {
+                x.prev = ptr::null_mut();
+                x.next = ptr::null_mut();
+            }
+        }
+    }
+
+    /// Returns the structure which embeds the element at the `offset`.
+    ///
+    /// # Safety
+    ///
+    /// The element must be embedded in a `T` at the `offset`.
+    pub unsafe fn data<T>(&self, offset: usize) -> NonNull<T> {
+        NonNull::new_unchecked(self.as_ptr().cast::<u8>().sub(offset).cast())
+    }
+
+    pub fn iter(&self) -> Iter {
+        Iter {
+            head: self,
+            next: unsafe { self.as_raw().next },
+        }
+    }
+
+    fn element(&self, p: *mut ffi::ngx_queue_t) -> Option<&QueueRef> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(unsafe { QueueRef::from_ptr(p) })
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    head: &'a QueueRef,
+    next: *mut ffi::ngx_queue_t,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a QueueRef;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() || ptr::eq(self.next, self.head.as_ptr()) {
+            None
+        } else {
+            unsafe {
+                let q = QueueRef::from_ptr(self.next);
+
+                self.next = q.as_raw().next;
+
+                Some(q)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::MaybeUninit;
+
+    use super::*;
+
+    fn new_queue(q: &mut MaybeUninit<ffi::ngx_queue_t>) -> &mut QueueRef {
+        unsafe { QueueRef::from_ptr_mut(q.as_mut_ptr()) }.init()
+    }
+
+    #[test]
+    fn queue() {
+        let mut h = MaybeUninit::uninit();
+        let mut a = MaybeUninit::uninit();
+        let mut b = MaybeUninit::uninit();
+
+        let h = new_queue(&mut h);
+        let a = new_queue(&mut a);
+        let b = new_queue(&mut b);
+
+        assert!(h.is_empty());
+        assert!(h.head().is_none());
+        assert_eq!(h.iter().count(), 0);
+
+        h.insert_head(a);
+        h.insert_tail(b);
+
+        assert!(!h.is_empty());
+        assert_eq!(h.head().unwrap().as_ptr(), a.as_ptr());
+        assert_eq!(h.last().unwrap().as_ptr(), b.as_ptr());
+        assert_eq!(h.iter().count(), 2);
+
+        a.remove();
+
+        assert_eq!(h.head().unwrap().as_ptr(), b.as_ptr());
+        assert_eq!(h.iter().count(), 1);
+
+        b.remove();
+
+        assert!(h.is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    ops::{Deref, DerefMut},
+    ptr::{null_mut, NonNull},
+    slice,
+};
+
+use foreign_types::{foreign_type, ForeignTypeRef};
+
+use crate::{core::Str, ffi, never_drop, AsRawMut, AsRawRef};
+
+foreign_type! {
+    pub unsafe type Value: Send {
+        type CType = ffi::ngx_variable_value_t;
+
+        fn drop = never_drop::<ffi::ngx_variable_value_t>;
+    }
+}
+
+impl Deref for ValueRef {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_bytes()
+    }
+}
+
+impl DerefMut for ValueRef {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_bytes_mut()
+    }
+}
+
+impl ValueRef {
+    property! {
+        /// The length of the value
+        len(): u32 { get; set; }
+    }
+
+    flag! {
+        /// The value is valid
+        valid { get; set; };
+
+        /// Do not cache result
+        no_cacheable { get; set; };
+
+        /// The variable was not found
+        not_found { get; set; };
+
+        /// Used internally by the logging module to mark values that require escaping on output.
+        escape { get; set; };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn set_value<S>(&mut self, s: S) -> &mut Self
+    where
+        S: Into<Str>,
+    {
+        let s: Str = s.into();
+
+        self.set_data(NonNull::new(s.as_ptr()))
+            .set_len(s.len() as u32)
+            .set_valid(true)
+            .set_no_cacheable(false)
+            .set_not_found(false)
+    }
+
+    pub fn data<T>(&self) -> Option<NonNull<T>> {
+        NonNull::new(unsafe { self.as_raw().data.cast() })
+    }
+
+    pub fn set_data<T>(&mut self, data: Option<NonNull<T>>) -> &mut Self {
+        unsafe { self.as_raw_mut().data = data.map_or_else(null_mut, |p| p.as_ptr().cast()) };
+
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            let r = self.as_raw();
+
+            slice::from_raw_parts(r.data as *const _, r.len() as usize)
+        }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            let r = self.as_raw();
+
+            slice::from_raw_parts_mut(r.data, r.len() as usize)
+        }
+    }
+}
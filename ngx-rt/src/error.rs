@@ -18,7 +18,9 @@ pub enum Error {
     #[error("internal error, {0}")]
     InternalError(isize),
 
-    #[error("config error, {0:?}")]
+    /// The message reported by nginx as `"name" directive <msg> in <file>:<line>`,
+    /// an empty message means the error has been reported.
+    #[error("{}", .0.to_string_lossy())]
     ConfigError(CString),
 
     #[error(transparent)]
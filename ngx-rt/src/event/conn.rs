@@ -4,10 +4,11 @@ use std::{
     ptr::{self, NonNull},
 };
 
+use bitflags::bitflags;
 use foreign_types::{foreign_type, ForeignTypeRef};
 
 use crate::{
-    core::{ConnRef, LogError, LogRef},
+    core::{ConnRef, LogError, LogRef, MSec},
     ffi, flag, native_callback, never_drop, property, AsRawMut, AsRawRef, Error,
 };
 
@@ -43,8 +44,16 @@ impl DerefMut for PeerConnRef {
 
 impl PeerConnRef {
     property! {
-        connection: &ConnRef;
-        tries: usize;
+        connection as &ConnRef;
+
+        /// The number of attempts left to connect to the upstream peers.
+        ///
+        /// Setting it to zero prevents further retries after a failed attempt.
+        tries: usize { get; set; };
+
+        /// The time when the first attempt was started.
+        start_time into MSec;
+
         type_: i32;
         rcvbuf: i32;
         log: &LogRef;
@@ -86,6 +95,20 @@ impl AsRef<LogRef> for PeerConnRef {
     }
 }
 
+bitflags! {
+    /// The state of the peer connection passed to [`FreePeerFn`].
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PeerState: usize {
+        /// The connection can be kept alive.
+        const KEEPALIVE = ffi::NGX_PEER_KEEPALIVE as usize;
+        /// The peer should be skipped for this request, but it is not failed.
+        const NEXT = ffi::NGX_PEER_NEXT as usize;
+        /// The attempt to communicate with the peer has failed.
+        const FAILED = ffi::NGX_PEER_FAILED as usize;
+    }
+}
+
 #[native_callback]
 pub type GetPeerFn<T> = fn(pc: &PeerConnRef, data: Option<&T>) -> Result<(), Error>;
 
@@ -1,13 +1,13 @@
 use std::os::fd::{AsRawFd, RawFd};
 use std::ptr::NonNull;
 
-use foreign_types::foreign_type;
+use foreign_types::{foreign_type, ForeignTypeRef};
 use ngx_rt_derive::native_callback;
 
 use crate::{
     callback,
     core::{rbtree, ConnRef, LogRef},
-    ffi, flag, never_drop, property, AsRawRef,
+    ffi, flag, never_drop, property, AsRawMut, AsRawRef, AsResult, Error, NativeCallback,
 };
 
 foreign_type! {
@@ -76,6 +76,33 @@ impl EventRef {
     pub fn data<T>(&self) -> Option<&T> {
         unsafe { NonNull::new(self.as_raw().data).map(|p| p.cast::<T>().as_ref()) }
     }
+
+    pub fn set_handler(&mut self, h: <HandlerFn as NativeCallback>::CType) -> &mut Self {
+        unsafe { self.as_raw_mut().handler = Some(h) }
+        self
+    }
+
+    /// Schedules the read event to be reported by the event notification mechanism.
+    pub fn handle_read(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi::ngx_handle_read_event(self.as_ptr(), 0)
+                .ok()
+                .map(|_| ())
+                .map_err(Error::from)
+        }
+    }
+
+    /// Schedules the write event to be reported by the event notification mechanism.
+    ///
+    /// The `lowat` is the minimal free space in the socket send buffer to report the event.
+    pub fn handle_write(&mut self, lowat: usize) -> Result<(), Error> {
+        unsafe {
+            ffi::ngx_handle_write_event(self.as_ptr(), lowat)
+                .ok()
+                .map(|_| ())
+                .map_err(Error::from)
+        }
+    }
 }
 
 #[native_callback]
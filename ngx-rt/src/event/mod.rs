@@ -1,6 +1,8 @@
 mod conn;
 mod evt;
+mod module;
 pub mod timer;
 
-pub use self::conn::{FreePeerFn, GetPeerFn, PeerConn, PeerConnRef};
+pub use self::conn::{FreePeerFn, GetPeerFn, PeerConn, PeerConnRef, PeerState};
 pub use self::evt::{Event, EventRef};
+pub use self::module::{conf, core_module, module};
@@ -0,0 +1,28 @@
+use std::ptr::NonNull;
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{CycleRef, ModuleRef},
+    ffi, AsRawRef,
+};
+
+/// The `events` block module.
+pub fn module() -> &'static ModuleRef {
+    unsafe { ModuleRef::from_ptr(&mut ffi::ngx_events_module as *mut _) }
+}
+
+/// The `event_core` module.
+pub fn core_module() -> &'static ModuleRef {
+    unsafe { ModuleRef::from_ptr(&mut ffi::ngx_event_core_module as *mut _) }
+}
+
+/// Returns the configuration of the event module `m` in the `events` block.
+pub fn conf<'a, T>(cycle: &'a CycleRef, m: &ModuleRef) -> Option<&'a T> {
+    unsafe {
+        let ctx = NonNull::new(cycle.as_raw().conf_ctx.add(module().index()).read())?;
+        let confs = NonNull::new(ctx.cast::<*mut *mut T>().as_ptr().read())?;
+
+        confs.as_ptr().add(m.ctx_index()).read().as_ref()
+    }
+}
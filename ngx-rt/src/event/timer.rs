@@ -128,15 +128,21 @@ impl EventRef {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::mem::zeroed;
+    use std::sync::Mutex;
 
     use crate::core::Log;
 
     use super::*;
 
+    /// Serializes the tests which add events to the global timer rbtree.
+    pub(crate) static LOCK: Mutex<()> = Mutex::new(());
+
     #[test]
     fn timer() {
+        let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
         // the timer rbtree should be empty
 
         assert!(no_timers_left());
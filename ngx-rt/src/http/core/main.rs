@@ -1,7 +1,22 @@
+use std::{
+    ffi::{c_void, CStr},
+    fmt, iter,
+    mem::MaybeUninit,
+    ptr,
+};
+
 use foreign_types::{foreign_type, ForeignTypeRef};
 use num_enum::FromPrimitive;
 
-use crate::{core::ArrayRef, ffi, http::HandlerFn, never_drop, AsRawMut, AsRawRef, NativeCallback};
+use crate::{
+    callback,
+    core::{ArrayRef, Code, ModuleRef},
+    ffi,
+    http::HandlerFn,
+    never_drop, property, AsRawMut, AsRawRef, NativeCallback,
+};
+
+use super::owner::tracked_by;
 
 foreign_type! {
     pub unsafe type MainConf: Send {
@@ -20,11 +35,92 @@ impl MainConfRef {
         unsafe { PhaseRef::from_ptr_mut(&mut self.as_raw_mut().phases[p as usize] as *mut _) }
     }
 
-    pub fn push_handler(&mut self, p: Phases, h: <HandlerFn as NativeCallback>::CType) {
-        self.phases_mut(p).handlers_mut().push(Some(h));
+    /// Adds the handler to the phase.
+    ///
+    /// The handlers of a phase are called in the reverse order they were added,
+    /// so the pushed handler is called before the handlers registered earlier.
+    ///
+    /// Returns [`Code::ERROR`] if the phase does not accept handlers,
+    /// see [`Phases::accepts_handlers`], or the handler can't be allocated.
+    pub fn push_handler(
+        &mut self,
+        p: Phases,
+        h: <HandlerFn as NativeCallback>::CType,
+    ) -> Result<(), Code> {
+        if !p.accepts_handlers() {
+            return Err(Code::ERROR);
+        }
+
+        self.phases_mut(p)
+            .handlers_mut()
+            .push(Some(h))
+            .map(|_| ())
+            .ok_or(Code::ERROR)
+    }
+
+    /// Inserts the handler to the phase at the `order` in which the handlers are called.
+    ///
+    /// Returns [`Code::DECLINED`] if no handler of the phase belongs to the module
+    /// in [`Order::Before`] or [`Order::After`], see [`PhaseRef::position`],
+    /// or [`Code::ERROR`] if the phase does not accept handlers.
+    pub fn insert_handler(
+        &mut self,
+        p: Phases,
+        order: Order,
+        h: <HandlerFn as NativeCallback>::CType,
+    ) -> Result<(), Code> {
+        if !p.accepts_handlers() {
+            return Err(Code::ERROR);
+        }
+
+        let phase = self.phases_mut(p);
+        let idx = match order {
+            Order::First => phase.handlers().len(),
+            Order::Last => 0,
+            Order::Before(m) => phase.position(m).ok_or(Code::DECLINED)?.1 + 1,
+            Order::After(m) => phase.position(m).ok_or(Code::DECLINED)?.0,
+        };
+
+        phase
+            .handlers_mut()
+            .insert(idx, Some(h))
+            .map(|_| ())
+            .ok_or(Code::ERROR)
+    }
+
+    /// Returns the phase handlers in the order they are called,
+    /// compiled by `ngx_http_init_phase_handlers` after the `postconfiguration` callbacks.
+    pub fn phase_engine(&self) -> impl Iterator<Item = &PhaseHandlerRef> {
+        let mut p = unsafe { self.as_raw().phase_engine.handlers };
+
+        iter::from_fn(move || unsafe {
+            p.as_ref().filter(|ph| ph.checker.is_some()).map(|_| {
+                let ph = PhaseHandlerRef::from_ptr(p);
+
+                p = p.add(1);
+
+                ph
+            })
+        })
     }
 }
 
+/// The order of a handler within the phase.
+#[derive(Clone, Copy)]
+pub enum Order<'a> {
+    /// Called before all the handlers of the phase.
+    First,
+
+    /// Called after all the handlers of the phase.
+    Last,
+
+    /// Called before the handlers which belong to the module, see [`PhaseRef::position`].
+    Before(&'a ModuleRef),
+
+    /// Called after the handlers which belong to the module, see [`PhaseRef::position`].
+    After(&'a ModuleRef),
+}
+
 foreign_type! {
     pub unsafe type Phase: Send {
         type CType = ffi::ngx_http_phase_t;
@@ -41,28 +137,434 @@ impl PhaseRef {
     pub fn handlers_mut(&mut self) -> &mut ArrayRef<ffi::ngx_http_handler_pt> {
         unsafe { ArrayRef::from_ptr_mut(&mut self.as_raw_mut().handlers as *mut _) }
     }
+
+    /// Returns the first and the last index of the handlers which belong to the module.
+    ///
+    /// The handler belongs to the module if it was added in the `postconfiguration` callback
+    /// of the module, which is tracked after [`track_handlers`](super::track_handlers) is called.
+    ///
+    /// Otherwise, the handler belongs to the module if it is an exported symbol prefixed
+    /// with the module name, e.g. `ngx_http_foo_handler` for `ngx_http_foo_module`,
+    /// or it is defined in the shared object of a dynamic module.
+    /// The `static` handlers of the modules built into nginx can be found only if tracked.
+    pub fn position(&self, m: &ModuleRef) -> Option<(usize, usize)> {
+        let handlers = self.handlers();
+        let owned = |h: &Option<HandlerFn>| h.is_some_and(|h| owned_by(h.0 as *const _, m));
+
+        handlers
+            .iter()
+            .position(owned)
+            .zip(handlers.iter().rposition(owned))
+    }
+}
+
+foreign_type! {
+    /// A handler of the compiled phase engine.
+    pub unsafe type PhaseHandler: Send {
+        type CType = ffi::ngx_http_phase_handler_t;
+
+        fn drop = never_drop::<ffi::ngx_http_phase_handler_t>;
+    }
 }
 
+impl PhaseHandlerRef {
+    property! {
+        /// The index of the handler to jump to, e.g. the first handler of the next phase.
+        next: usize;
+    }
+
+    callback! {
+        handler: HandlerFn;
+    }
+
+    /// Returns the symbol name of the phase checker.
+    pub fn checker_name(&self) -> Option<&'static CStr> {
+        unsafe { self.as_raw().checker }.and_then(|f| symbol(f as *const _))
+    }
+
+    /// Returns the symbol name of the handler.
+    pub fn handler_name(&self) -> Option<&'static CStr> {
+        unsafe { self.as_raw().handler }.and_then(|f| symbol(f as *const _))
+    }
+}
+
+impl fmt::Debug for PhaseHandlerRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = unsafe { self.as_raw() };
+
+        f.debug_struct("PhaseHandler")
+            .field(
+                "checker",
+                &self.checker_name().map_or_else(
+                    || format!("{:?}", r.checker.map(|f| f as *const c_void)),
+                    |s| s.to_string_lossy().into_owned(),
+                ),
+            )
+            .field(
+                "handler",
+                &self.handler_name().map_or_else(
+                    || format!("{:?}", r.handler.map(|f| f as *const c_void)),
+                    |s| s.to_string_lossy().into_owned(),
+                ),
+            )
+            .field("next", &r.next)
+            .finish()
+    }
+}
+
+fn dl_info(p: *const c_void) -> Option<libc::Dl_info> {
+    let mut info = MaybeUninit::<libc::Dl_info>::zeroed();
+
+    unsafe {
+        if libc::dladdr(p, info.as_mut_ptr()) == 0 {
+            None
+        } else {
+            Some(info.assume_init())
+        }
+    }
+}
+
+fn symbol(p: *const c_void) -> Option<&'static CStr> {
+    dl_info(p)
+        .filter(|info| ptr::eq(info.dli_saddr, p) && !info.dli_sname.is_null())
+        .map(|info| unsafe { CStr::from_ptr(info.dli_sname) })
+}
+
+fn owned_by(h: *const c_void, m: &ModuleRef) -> bool {
+    if let Some(owned) = tracked_by(h as usize, m) {
+        return owned;
+    }
+
+    let name = m.name().to_bytes();
+    let prefix = name.strip_suffix(b"_module").unwrap_or(name);
+
+    if symbol(h).is_some_and(|sym| {
+        sym.to_bytes()
+            .strip_prefix(prefix)
+            .is_some_and(|s| s.starts_with(b"_"))
+    }) {
+        return true;
+    }
+
+    match (
+        dl_info(h),
+        dl_info(m.as_ptr().cast()),
+        dl_info(super::module().as_ptr().cast()),
+    ) {
+        (Some(hi), Some(mi), Some(ci)) => {
+            ptr::eq(hi.dli_fbase, mi.dli_fbase) && !ptr::eq(mi.dli_fbase, ci.dli_fbase)
+        }
+        _ => false,
+    }
+}
+
+/// The request processing phases.
+///
+/// Each phase has a checker which interprets the [`PhaseResult`](super::PhaseResult)
+/// returned by its handlers.
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive)]
 pub enum Phases {
+    /// The first phase, e.g. the realip module.
+    ///
+    /// `Ok` moves to the next phase, `Declined` calls the next handler,
+    /// `Again` and `Done` suspend the request, and `Status` finalizes it.
     #[default]
     PostRead = ffi::ngx_http_phases_NGX_HTTP_POST_READ_PHASE,
 
+    /// The rewrite directives defined in a `server` block.
+    ///
+    /// `Declined` calls the next handler, `Done` suspends the request,
+    /// any other result finalizes it.
     ServerRewrite = ffi::ngx_http_phases_NGX_HTTP_SERVER_REWRITE_PHASE,
 
+    /// The location lookup, does not accept handlers.
     FindConfig = ffi::ngx_http_phases_NGX_HTTP_FIND_CONFIG_PHASE,
+    /// The rewrite directives defined in a `location` block.
+    ///
+    /// `Declined` calls the next handler, `Done` suspends the request,
+    /// any other result finalizes it.
     Rewrite = ffi::ngx_http_phases_NGX_HTTP_REWRITE_PHASE,
+    /// The redirect after the URI rewrite, does not accept handlers.
     PostRewrite = ffi::ngx_http_phases_NGX_HTTP_POST_REWRITE_PHASE,
 
+    /// The limits before the access control, e.g. the limit_conn and limit_req modules.
+    ///
+    /// `Ok` moves to the next phase, `Declined` calls the next handler,
+    /// `Again` and `Done` suspend the request, and `Status` finalizes it.
     Preaccess = ffi::ngx_http_phases_NGX_HTTP_PREACCESS_PHASE,
 
+    /// The access control, e.g. the access and auth_basic modules.
+    ///
+    /// `Declined` calls the next handler, `Again` and `Done` suspend the request.
+    /// With `satisfy all`, the default, `Ok` also calls the next handler,
+    /// so the request is allowed only if no handler denies it.
+    /// With `satisfy any`, `Ok` allows the request and skips the remaining handlers,
+    /// and `403`/`401` let the next handler decide.
+    /// Any other `Status` finalizes the request. The phase is skipped for subrequests.
     Access = ffi::ngx_http_phases_NGX_HTTP_ACCESS_PHASE,
+    /// The result of the access control, does not accept handlers.
     PostAccess = ffi::ngx_http_phases_NGX_HTTP_POST_ACCESS_PHASE,
 
+    /// The actions before generating the content, e.g. the try_files and mirror modules.
+    ///
+    /// `Ok` moves to the next phase, `Declined` calls the next handler,
+    /// `Again` and `Done` suspend the request, and `Status` finalizes it.
     Precontent = ffi::ngx_http_phases_NGX_HTTP_PRECONTENT_PHASE,
 
+    /// The content generation.
+    ///
+    /// The handlers are called only if the location has no content handler, e.g. `proxy_pass`.
+    /// `Declined` calls the next handler, any other result finalizes the request.
     Content = ffi::ngx_http_phases_NGX_HTTP_CONTENT_PHASE,
 
+    /// The request logging, the results are ignored.
     Log = ffi::ngx_http_phases_NGX_HTTP_LOG_PHASE,
 }
+
+impl Phases {
+    /// Returns `false` for the phases which nginx runs without the handlers of the modules,
+    /// e.g. [`Phases::FindConfig`], [`Phases::PostRewrite`] and [`Phases::PostAccess`].
+    pub fn accepts_handlers(self) -> bool {
+        !matches!(
+            self,
+            Phases::FindConfig | Phases::PostRewrite | Phases::PostAccess
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{self, zeroed};
+
+    use foreign_types::ForeignType;
+
+    use crate::{
+        core::{Conf, ConfRef, CycleRef, Log, Pool},
+        http::core::{main_conf_mut, track_handlers},
+    };
+
+    use super::*;
+
+    unsafe extern "C" fn access(_r: *mut ffi::ngx_http_request_t) -> ffi::ngx_int_t {
+        ffi::NGX_DECLINED as ffi::ngx_int_t
+    }
+
+    unsafe extern "C" fn auth(_r: *mut ffi::ngx_http_request_t) -> ffi::ngx_int_t {
+        ffi::NGX_DECLINED as ffi::ngx_int_t
+    }
+
+    unsafe extern "C" fn before(_r: *mut ffi::ngx_http_request_t) -> ffi::ngx_int_t {
+        ffi::NGX_DECLINED as ffi::ngx_int_t
+    }
+
+    unsafe extern "C" fn after(_r: *mut ffi::ngx_http_request_t) -> ffi::ngx_int_t {
+        ffi::NGX_DECLINED as ffi::ngx_int_t
+    }
+
+    unsafe extern "C" fn checker(
+        _r: *mut ffi::ngx_http_request_t,
+        _ph: *mut ffi::ngx_http_phase_handler_t,
+    ) -> ffi::ngx_int_t {
+        ffi::NGX_OK as ffi::ngx_int_t
+    }
+
+    unsafe extern "C" fn access_postconfiguration(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t {
+        add_handler(cf, access)
+    }
+
+    unsafe extern "C" fn auth_postconfiguration(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t {
+        add_handler(cf, auth)
+    }
+
+    unsafe fn add_handler(
+        cf: *mut ffi::ngx_conf_t,
+        h: <HandlerFn as NativeCallback>::CType,
+    ) -> ffi::ngx_int_t {
+        ConfRef::from_ptr(cf)
+            .as_http_context()
+            .and_then(main_conf_mut)
+            .unwrap()
+            .push_handler(Phases::Access, h)
+            .map_or_else(ffi::ngx_int_t::from, |_| ffi::NGX_OK as ffi::ngx_int_t)
+    }
+
+    unsafe fn http_module(
+        postconfiguration: unsafe extern "C" fn(*mut ffi::ngx_conf_t) -> ffi::ngx_int_t,
+    ) -> *mut ffi::ngx_module_t {
+        let ctx: *mut ffi::ngx_http_module_t = Box::into_raw(Box::new(zeroed()));
+        let module: *mut ffi::ngx_module_t = Box::into_raw(Box::new(zeroed()));
+
+        (*ctx).postconfiguration = Some(postconfiguration);
+        (*module).ctx = ctx.cast();
+        (*module).type_ = ffi::NGX_HTTP_MODULE as _;
+
+        module
+    }
+
+    fn addr(h: <HandlerFn as NativeCallback>::CType) -> usize {
+        h as usize
+    }
+
+    fn addr_of_postconfiguration(
+        f: unsafe extern "C" fn(*mut ffi::ngx_conf_t) -> ffi::ngx_int_t,
+    ) -> usize {
+        f as usize
+    }
+
+    fn addrs(phase: &PhaseRef) -> Vec<usize> {
+        phase
+            .handlers()
+            .iter()
+            .map(|h| h.map_or(0, |h| h.0 as usize))
+            .collect()
+    }
+
+    #[test]
+    fn insert_handler() {
+        let pool = Pool::new(4096, Log::stderr()).unwrap();
+
+        unsafe {
+            let access_module = http_module(access_postconfiguration);
+            let auth_module = http_module(auth_postconfiguration);
+            let other_module = http_module(auth_postconfiguration);
+
+            let modules = Box::into_raw(Box::new([access_module, auth_module, ptr::null_mut()]));
+            let cycle: *mut ffi::ngx_cycle_t = Box::into_raw(Box::new(zeroed()));
+
+            (*cycle).pool = pool.as_ptr();
+            (*cycle).log = Log::stderr().as_ptr();
+            (*cycle).modules = (*modules).as_mut_ptr();
+            (*cycle).modules_n = 2;
+
+            let cmcf: *mut ffi::ngx_http_core_main_conf_t = Box::into_raw(Box::new(zeroed()));
+
+            (*cmcf).phases[Phases::Access as usize].handlers = *ffi::ngx_array_create(
+                pool.as_ptr(),
+                1,
+                mem::size_of::<ffi::ngx_http_handler_pt>(),
+            );
+
+            let main_conf = Box::into_raw(Box::new([cmcf.cast::<c_void>()]));
+            let ctx: *mut ffi::ngx_http_conf_ctx_t = Box::into_raw(Box::new(zeroed()));
+
+            (*ctx).main_conf = (*main_conf).as_mut_ptr();
+            ffi::ngx_http_core_module.ctx_index = 0;
+
+            let cf = Conf::with_cycle(CycleRef::from_ptr(cycle));
+
+            (*cf.as_ptr()).ctx = ctx.cast();
+            (*cf.as_ptr()).module_type = ffi::NGX_HTTP_MODULE as _;
+
+            // called by `ngx_http_block()` like the `preconfiguration` and `postconfiguration` callbacks,
+            // the callbacks are wrapped once even if more modules opt in
+            track_handlers(&cf).unwrap();
+            track_handlers(&cf).unwrap();
+
+            for m in [access_module, auth_module] {
+                let ctx = (*m).ctx.cast::<ffi::ngx_http_module_t>();
+
+                assert_eq!((*ctx).postconfiguration.unwrap()(cf.as_ptr()), 0);
+            }
+
+            assert_eq!(
+                (*(*access_module).ctx.cast::<ffi::ngx_http_module_t>())
+                    .postconfiguration
+                    .map(|f| f as usize),
+                Some(addr_of_postconfiguration(access_postconfiguration)),
+                "the callback should be restored"
+            );
+
+            let cmcf = MainConfRef::from_ptr_mut(cmcf);
+            let phase = cmcf.phases(Phases::Access);
+
+            assert_eq!(
+                phase.position(ModuleRef::from_ptr(access_module)),
+                Some((0, 0))
+            );
+            assert_eq!(
+                phase.position(ModuleRef::from_ptr(auth_module)),
+                Some((1, 1))
+            );
+            assert_eq!(phase.position(ModuleRef::from_ptr(other_module)), None);
+
+            // the handlers are called in the reverse order, e.g. `auth` before `access`
+            cmcf.insert_handler(
+                Phases::Access,
+                Order::Before(ModuleRef::from_ptr(access_module)),
+                before,
+            )
+            .unwrap();
+            cmcf.insert_handler(
+                Phases::Access,
+                Order::After(ModuleRef::from_ptr(auth_module)),
+                after,
+            )
+            .unwrap();
+
+            assert_eq!(
+                addrs(cmcf.phases(Phases::Access)),
+                [addr(access), addr(before), addr(after), addr(auth)]
+            );
+
+            cmcf.insert_handler(Phases::Access, Order::First, before)
+                .unwrap();
+            cmcf.insert_handler(Phases::Access, Order::Last, after)
+                .unwrap();
+
+            assert_eq!(
+                addrs(cmcf.phases(Phases::Access)),
+                [
+                    addr(after),
+                    addr(access),
+                    addr(before),
+                    addr(after),
+                    addr(auth),
+                    addr(before)
+                ]
+            );
+
+            assert_eq!(
+                cmcf.insert_handler(
+                    Phases::Access,
+                    Order::Before(ModuleRef::from_ptr(other_module)),
+                    before
+                ),
+                Err(Code::DECLINED)
+            );
+
+            // nginx runs no handlers in the phase
+            assert_eq!(
+                cmcf.push_handler(Phases::PostAccess, before),
+                Err(Code::ERROR)
+            );
+            assert_eq!(
+                cmcf.insert_handler(Phases::FindConfig, Order::First, before),
+                Err(Code::ERROR)
+            );
+        }
+    }
+
+    #[test]
+    fn phase_engine() {
+        unsafe {
+            let cmcf: *mut ffi::ngx_http_core_main_conf_t = Box::into_raw(Box::new(zeroed()));
+            let engine: *mut [ffi::ngx_http_phase_handler_t; 3] = Box::into_raw(Box::new(zeroed()));
+
+            (*engine)[0].checker = Some(checker);
+            (*engine)[0].handler = Some(auth);
+            (*engine)[0].next = 2;
+            (*engine)[1].checker = Some(checker);
+            (*engine)[1].handler = Some(access);
+            (*engine)[1].next = 2;
+            (*cmcf).phase_engine.handlers = (*engine).as_mut_ptr();
+
+            let handlers = MainConfRef::from_ptr(cmcf)
+                .phase_engine()
+                .map(|ph| (ph.as_raw().handler.map(|h| h as usize), ph.next()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(handlers, [(Some(addr(auth)), 2), (Some(addr(access)), 2)]);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 mod loc;
 mod main;
 mod module;
+mod owner;
+mod phase;
 mod srv;
 
 pub use self::loc::LocConfRef;
-pub use self::main::{MainConfRef, PhaseRef, Phases};
+pub use self::main::{MainConfRef, Order, PhaseHandler, PhaseHandlerRef, PhaseRef, Phases};
 pub use self::module::{
     loc_conf, loc_conf_mut, main_conf, main_conf_mut, module, srv_conf, srv_conf_mut,
 };
+pub use self::owner::track_handlers;
+pub use self::phase::PhaseResult;
 pub use self::srv::SrvConfRef;
@@ -0,0 +1,205 @@
+use std::{
+    ffi::c_void,
+    ptr::NonNull,
+    slice,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{Code, ConfRef, ModuleRef, ModuleType},
+    ffi,
+    http::core::main_conf,
+    AsRawRef,
+};
+
+use super::MainConfRef;
+
+type PostConfiguration = unsafe extern "C" fn(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t;
+
+/// The phase handlers added by the HTTP modules in their `postconfiguration` callbacks.
+static TRACKER: Mutex<Tracker> = Mutex::new(Tracker::new());
+
+struct Tracker {
+    /// The cycle whose configuration is tracked.
+    cycle: usize,
+
+    /// The wrapped callbacks, in the order of the modules.
+    wrapped: Vec<Wrapped>,
+
+    /// The address of the handlers, with the address of the module which added it.
+    handlers: Vec<(usize, usize)>,
+}
+
+struct Wrapped {
+    module: *mut ffi::ngx_module_t,
+    ctx: *mut ffi::ngx_http_module_t,
+    postconfiguration: PostConfiguration,
+}
+
+// Only accessed by the master process while parsing the configuration.
+unsafe impl Send for Tracker {}
+
+impl Tracker {
+    const fn new() -> Self {
+        Tracker {
+            cycle: 0,
+            wrapped: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+
+    fn lock() -> MutexGuard<'static, Tracker> {
+        TRACKER.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Restores the callbacks which have not been called, e.g. the configuration failed.
+    fn restore(&mut self) {
+        for w in self.wrapped.drain(..) {
+            if w.is_wrapped() {
+                unsafe { (*w.ctx).postconfiguration = Some(w.postconfiguration) };
+            }
+        }
+
+        self.cycle = 0;
+    }
+
+    /// Returns `true` if the handlers of the configuration are tracked.
+    fn is_tracking(&self) -> bool {
+        self.cycle != 0
+    }
+}
+
+impl Wrapped {
+    /// Returns `true` if the callback of the module has not been called yet.
+    fn is_wrapped(&self) -> bool {
+        unsafe {
+            (*self.ctx).postconfiguration.map(|f| f as usize)
+                == Some(postconfiguration as PostConfiguration as usize)
+        }
+    }
+}
+
+/// Tracks the phase handlers added by each HTTP module in its `postconfiguration` callback,
+/// should be called in the `preconfiguration` callback of a module which inserts its handlers
+/// with [`Order::Before`](super::Order::Before) or [`Order::After`](super::Order::After).
+///
+/// The `postconfiguration` callbacks of the HTTP modules are wrapped until they are called,
+/// to record the handlers which belong to the module, see [`PhaseRef::position`](super::PhaseRef::position).
+/// It is called once per configuration, even if more modules opt in.
+pub fn track_handlers(cf: &ConfRef) -> Result<(), Code> {
+    let mut tracker = Tracker::lock();
+    let cycle = cf.cycle().as_ptr() as usize;
+
+    if tracker.cycle == cycle {
+        return Ok(());
+    }
+
+    tracker.restore();
+    tracker.handlers.clear();
+    tracker.cycle = cycle;
+
+    for m in cf.cycle().modules() {
+        if m.ty() != ModuleType::Http {
+            continue;
+        }
+
+        unsafe {
+            let module = m.as_ptr();
+            let ctx = (*module).ctx.cast::<ffi::ngx_http_module_t>();
+
+            if let Some(f) = (*ctx).postconfiguration {
+                if f as usize != postconfiguration as PostConfiguration as usize {
+                    tracker.wrapped.push(Wrapped {
+                        module,
+                        ctx,
+                        postconfiguration: f,
+                    });
+
+                    (*ctx).postconfiguration = Some(postconfiguration);
+                }
+            }
+        }
+    }
+
+    drop(tracker);
+
+    unsafe {
+        cf.pool()
+            .add_cleanup(Some(restore), NonNull::new(cf.cycle().as_ptr()))
+            .map(|_| ())
+            .map_err(|_| Code::ERROR)
+    }
+}
+
+/// Returns `true` if the handler was added by the module in its `postconfiguration` callback,
+/// or `None` if the handlers are not tracked, see [`track_handlers`].
+pub(crate) fn tracked_by(h: usize, m: &ModuleRef) -> Option<bool> {
+    let m = m.as_ptr() as usize;
+    let tracker = Tracker::lock();
+
+    tracker.is_tracking().then(|| {
+        tracker
+            .handlers
+            .iter()
+            .any(|&(module, handler)| module == m && handler == h)
+    })
+}
+
+/// Restores the callbacks when the cycle is destroyed, unless another cycle is tracked.
+unsafe extern "C" fn restore(data: *mut c_void) {
+    let mut tracker = Tracker::lock();
+
+    if tracker.cycle == data as usize {
+        tracker.restore();
+    }
+}
+
+unsafe extern "C" fn postconfiguration(cf: *mut ffi::ngx_conf_t) -> ffi::ngx_int_t {
+    // `ngx_http_block()` calls the callbacks in the order of the modules,
+    // so the first module which is still wrapped is being called.
+    let (module, f) = {
+        let tracker = Tracker::lock();
+        let Some(w) = tracker.wrapped.iter().find(|w| w.is_wrapped()) else {
+            return Code::ERROR.into();
+        };
+
+        (*w.ctx).postconfiguration = Some(w.postconfiguration);
+
+        (w.module, w.postconfiguration)
+    };
+
+    let cmcf = ConfRef::from_ptr(cf).as_http_context().and_then(main_conf);
+    let before = cmcf.map(handlers).unwrap_or_default();
+
+    let rc = f(cf);
+
+    if let Some(cmcf) = cmcf {
+        Tracker::lock().handlers.extend(
+            handlers(cmcf)
+                .into_iter()
+                .filter(|h| !before.contains(h))
+                .map(|h| (module as usize, h)),
+        );
+    }
+
+    rc
+}
+
+/// Returns the address of the handlers of all phases.
+fn handlers(cmcf: &MainConfRef) -> Vec<usize> {
+    unsafe { cmcf.as_raw() }
+        .phases
+        .iter()
+        .filter(|ph| !ph.handlers.elts.is_null())
+        .flat_map(|ph| unsafe {
+            slice::from_raw_parts(
+                ph.handlers.elts.cast::<ffi::ngx_http_handler_pt>(),
+                ph.handlers.nelts,
+            )
+        })
+        .flatten()
+        .map(|&h| h as usize)
+        .collect()
+}
@@ -0,0 +1,97 @@
+use http::StatusCode;
+
+use crate::core::Code;
+
+/// The result of a phase handler.
+///
+/// How the result is interpreted depends on the phase checker,
+/// see [`Phases`](super::Phases) for the results valid in each phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseResult {
+    /// The request was handled, continue with the next phase.
+    Ok,
+
+    /// The request is not handled by the module, call the next handler.
+    Declined,
+
+    /// The request is suspended, the handler will be called again.
+    Again,
+
+    /// The request is suspended and continued elsewhere.
+    Done,
+
+    /// Finalize the request with the status.
+    Status(StatusCode),
+}
+
+impl From<PhaseResult> for isize {
+    fn from(r: PhaseResult) -> Self {
+        match r {
+            PhaseResult::Ok => Code::OK.into(),
+            PhaseResult::Declined => Code::DECLINED.into(),
+            PhaseResult::Again => Code::AGAIN.into(),
+            PhaseResult::Done => Code::DONE.into(),
+            PhaseResult::Status(status) => status.as_u16() as isize,
+        }
+    }
+}
+
+impl From<StatusCode> for PhaseResult {
+    fn from(status: StatusCode) -> Self {
+        PhaseResult::Status(status)
+    }
+}
+
+/// Converts a [`Code`] into the phase result.
+///
+/// The codes without a phase result counterpart, such as [`Code::ERROR`],
+/// finalize the request with `500 Internal Server Error`.
+impl From<Code> for PhaseResult {
+    fn from(code: Code) -> Self {
+        match code {
+            Code::OK => PhaseResult::Ok,
+            Code::DECLINED => PhaseResult::Declined,
+            Code::AGAIN => PhaseResult::Again,
+            Code::DONE => PhaseResult::Done,
+            _ => PhaseResult::Status(
+                code.as_status_code()
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            ),
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for PhaseResult
+where
+    T: Into<PhaseResult>,
+    E: Into<PhaseResult>,
+{
+    fn from(res: Result<T, E>) -> Self {
+        res.map_or_else(Into::into, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_result() {
+        assert_eq!(isize::from(PhaseResult::Declined), Code::DECLINED.into());
+        assert_eq!(isize::from(PhaseResult::Status(StatusCode::FORBIDDEN)), 403);
+
+        assert_eq!(PhaseResult::from(Code::AGAIN), PhaseResult::Again);
+        assert_eq!(
+            PhaseResult::from(Code::ERROR),
+            PhaseResult::Status(StatusCode::INTERNAL_SERVER_ERROR)
+        );
+        assert_eq!(
+            PhaseResult::from(Err::<StatusCode, _>(Code::DECLINED)),
+            PhaseResult::Declined
+        );
+        assert_eq!(
+            PhaseResult::from(Ok::<_, Code>(StatusCode::FORBIDDEN)),
+            PhaseResult::Status(StatusCode::FORBIDDEN)
+        );
+    }
+}
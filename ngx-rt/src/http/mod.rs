@@ -21,4 +21,4 @@ pub use self::req::{
     Body, BodyRef, ConnType, EventHandlerFn, HandlerFn, HeadersIn, HeadersInRef, HeadersOut,
     HeadersOutRef, Method, ModuleContext, Request, RequestRef, UnsafeModuleContext,
 };
-pub use self::var::{RawVar, Value, ValueRef, Var, VarRef};
+pub use self::var::{RawVar, Value, ValueRef, Var, VarRef, VariableIndex};
@@ -1,14 +1,17 @@
 use std::{ops::Deref, ptr::NonNull, slice};
 
 use bitflags::bitflags;
-use foreign_types::foreign_type;
+use foreign_types::{foreign_type, ForeignTypeRef};
 use num_enum::FromPrimitive;
 
 use crate::{
-    core::{BufRef, ConnRef, LogRef, PoolRef},
+    core::{ArrayRef, BufRef, ConnRef, LogRef, PoolRef},
     ffi, flag,
-    http::{upstream::UpstreamRef, UnsafeLocConf, UnsafeMainConf, UnsafeSrvConf},
-    native_callback, never_drop, property, str, AsRawRef, Error,
+    http::{
+        upstream::{StateRef, UpstreamRef},
+        UnsafeLocConf, UnsafeMainConf, UnsafeSrvConf,
+    },
+    native_callback, never_drop, property, str, AsRawRef, Error, FromRawRef,
 };
 
 use super::{body::BodyRef, HeadersInRef, HeadersOutRef, Method};
@@ -272,6 +275,16 @@ impl RequestRef {
         }
     }
 
+    /// The states of the upstream attempts made for this request.
+    pub fn upstream_states(&self) -> impl Iterator<Item = &StateRef> {
+        unsafe {
+            ArrayRef::<ffi::ngx_http_upstream_state_t>::from_raw(self.as_raw().upstream_states)
+                .into_iter()
+                .flat_map(|a| a.iter())
+                .map(|s| StateRef::from_ptr(s as *const _ as *mut _))
+        }
+    }
+
     /// Bitmask showing which modules have buffered the output produced by the request.
     pub fn buffered(&self) -> Buffered {
         unsafe { Buffered::from_bits_truncate(self.as_raw().buffered()) }
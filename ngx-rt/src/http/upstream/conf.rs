@@ -2,9 +2,9 @@ use std::ptr::NonNull;
 
 use foreign_types::foreign_type;
 
-use crate::{ffi, http::UnsafeSrvConf, never_drop, property, AsRawRef};
+use crate::{core::MSec, ffi, http::UnsafeSrvConf, never_drop, property, AsRawRef};
 
-use super::PeerRef;
+use super::{Failure, PeerRef};
 
 foreign_type! {
     pub unsafe type MainConf: Send {
@@ -31,3 +31,35 @@ impl UnsafeSrvConf for SrvConfRef {
 impl SrvConfRef {
     property!(&mut peer: &mut PeerRef);
 }
+
+foreign_type! {
+    pub unsafe type UpstreamConf: Send {
+        type CType = ffi::ngx_http_upstream_conf_t;
+
+        fn drop = never_drop::<ffi::ngx_http_upstream_conf_t>;
+    }
+}
+
+impl UpstreamConfRef {
+    property! {
+        upstream as &SrvConfRef;
+
+        connect_timeout into MSec;
+        send_timeout into MSec;
+        read_timeout into MSec;
+
+        /// Limits the time during which a request can be passed to the next server.
+        next_upstream_timeout into MSec;
+
+        /// Limits the number of possible tries for passing a request to the next server.
+        next_upstream_tries: usize;
+
+        buffer_size: usize;
+        limit_rate: usize;
+    }
+
+    /// The failures in which a request should be passed to the next server.
+    pub fn next_upstream(&self) -> Failure {
+        Failure::from_bits_truncate(unsafe { self.as_raw().next_upstream as u32 })
+    }
+}
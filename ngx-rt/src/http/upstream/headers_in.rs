@@ -0,0 +1,61 @@
+use foreign_types::foreign_type;
+
+use crate::{ffi, never_drop};
+
+foreign_type! {
+    pub unsafe type HeadersIn: Send {
+        type CType = ffi::ngx_http_upstream_headers_in_t;
+
+        fn drop = never_drop::<ffi::ngx_http_upstream_headers_in_t>;
+    }
+}
+
+impl HeadersInRef {
+    header! {
+        status;
+        date;
+        server;
+        connection;
+        expires;
+        etag;
+        x_accel_expires;
+        x_accel_redirect;
+        x_accel_limit_rate;
+        content_type;
+        content_length;
+        last_modified;
+        location;
+        refresh;
+        www_authenticate;
+        transfer_encoding;
+        vary;
+        cache_control;
+        set_cookie;
+    }
+
+    str! {
+        /// The status line of the upstream response.
+        &status_line;
+    }
+
+    property! {
+        /// The upstream response headers.
+        headers: Headers;
+
+        /// The upstream response trailers.
+        trailers: Headers;
+
+        /// The status code of the upstream response.
+        status_n: usize;
+
+        content_length_n: i64;
+        last_modified_time: i64;
+    }
+
+    flag! {
+        connection_close;
+        chunked;
+        no_cache;
+        expired;
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    ffi::c_void,
+    mem,
+    ptr::{self, NonNull},
+    slice,
+    time::Duration,
+};
+
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{time, Code, MSec, PoolRef, QueueRef},
+    event::{EventRef, FreePeerFn, GetPeerFn, PeerConnRef, PeerState},
+    ffi, native_handler, AsRawMut, AsRawRef, Error,
+};
+
+use super::UpstreamRef;
+
+/// A cache of idle keepalive connections to the upstream servers.
+///
+/// It works like the `keepalive` directive of the `upstream` block,
+/// but can wrap the `get` and `free` callbacks of any balancer.
+#[repr(C)]
+pub struct Keepalive {
+    /// The maximum number of requests served through one keepalive connection.
+    pub requests: usize,
+    /// The maximum time during which requests can be served through one keepalive connection.
+    pub time: MSec,
+    /// The timeout during which an idle keepalive connection stays open.
+    pub timeout: MSec,
+
+    cache: ffi::ngx_queue_t,
+    free: ffi::ngx_queue_t,
+}
+
+#[repr(C)]
+struct Cached {
+    queue: ffi::ngx_queue_t,
+    keepalive: NonNull<Keepalive>,
+    connection: *mut ffi::ngx_connection_t,
+    sockaddr: ffi::ngx_sockaddr_t,
+    socklen: ffi::socklen_t,
+}
+
+#[repr(C)]
+struct Peer {
+    keepalive: NonNull<Keepalive>,
+    upstream: NonNull<ffi::ngx_http_upstream_t>,
+    data: *mut c_void,
+    get: ffi::ngx_event_get_peer_pt,
+    free: ffi::ngx_event_free_peer_pt,
+    set_session: ffi::ngx_event_set_peer_session_pt,
+    save_session: ffi::ngx_event_save_peer_session_pt,
+}
+
+impl Keepalive {
+    pub const DEFAULT_REQUESTS: usize = 1000;
+    pub const DEFAULT_TIME: Duration = Duration::from_secs(3600);
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Creates a cache which keeps up to `max_cached` idle connections.
+    ///
+    /// The cache is allocated from the `pool`, it should live as long as the upstream,
+    /// e.g. be created from the `init_upstream` callback with the configuration pool.
+    pub fn create(pool: &PoolRef, max_cached: usize) -> Result<&mut Keepalive, Error> {
+        let ka = pool.calloc::<Keepalive>().ok_or(Error::OutOfMemory)?;
+
+        ka.requests = Self::DEFAULT_REQUESTS;
+        ka.time = Self::DEFAULT_TIME.into();
+        ka.timeout = Self::DEFAULT_TIMEOUT.into();
+        ka.cache_mut().init();
+        ka.free_mut().init();
+
+        let items = unsafe { pool.palloc(mem::size_of::<Cached>() * max_cached) }.cast::<Cached>();
+
+        if items.is_null() && max_cached > 0 {
+            return Err(Error::OutOfMemory);
+        }
+
+        let keepalive = NonNull::from(&mut *ka);
+
+        for i in 0..max_cached {
+            let item = unsafe { &mut *items.add(i) };
+
+            item.keepalive = keepalive;
+            ka.free_mut().insert_head(item.queue_mut());
+        }
+
+        Ok(ka)
+    }
+
+    /// Wraps the peer callbacks of the upstream request to reuse the cached connections.
+    ///
+    /// It should be called from the `init` peer callback after the balancer
+    /// has set its own `get` and `free` callbacks.
+    pub fn init_peer(&mut self, pool: &PoolRef, u: &mut UpstreamRef) -> Result<(), Error> {
+        let (get, free) = u
+            .peer()
+            .get()
+            .zip(u.peer().free())
+            .ok_or(Error::InternalError(ffi::NGX_ERROR as isize))?;
+
+        self.wrap(pool, u, get, free)
+    }
+
+    /// Sets the peer callbacks of the upstream request to `get` and `free`
+    /// wrapped with the connection cache.
+    pub fn wrap(
+        &mut self,
+        pool: &PoolRef,
+        u: &mut UpstreamRef,
+        get: GetPeerFn,
+        free: FreePeerFn,
+    ) -> Result<(), Error> {
+        let upstream = NonNull::from(unsafe { u.as_raw_mut() });
+        let pc = unsafe { &mut u.as_raw_mut().peer };
+
+        let peer = pool
+            .allocate(Peer {
+                keepalive: NonNull::from(&mut *self),
+                upstream,
+                data: pc.data,
+                get: Some(get.0),
+                free: Some(free.0),
+                set_session: pc.set_session,
+                save_session: pc.save_session,
+            })
+            .ok_or(Error::OutOfMemory)?;
+
+        pc.data = peer as *mut Peer as *mut _;
+        pc.get = Some(ngx_rt_http_upstream_get_keepalive_peer);
+        pc.free = Some(ngx_rt_http_upstream_free_keepalive_peer);
+
+        if pc.set_session.is_some() {
+            pc.set_session = Some(ngx_rt_http_upstream_keepalive_set_session);
+        }
+        if pc.save_session.is_some() {
+            pc.save_session = Some(ngx_rt_http_upstream_keepalive_save_session);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of the cached connections.
+    pub fn len(&self) -> usize {
+        self.cache().iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache().is_empty()
+    }
+
+    fn cache(&self) -> &QueueRef {
+        unsafe { QueueRef::from_ptr(&self.cache as *const _ as *mut _) }
+    }
+
+    fn cache_mut(&mut self) -> &mut QueueRef {
+        unsafe { QueueRef::from_ptr_mut(&mut self.cache) }
+    }
+
+    fn free(&self) -> &QueueRef {
+        unsafe { QueueRef::from_ptr(&self.free as *const _ as *mut _) }
+    }
+
+    fn free_mut(&mut self) -> &mut QueueRef {
+        unsafe { QueueRef::from_ptr_mut(&mut self.free) }
+    }
+
+    fn find(&mut self, sa: *const ffi::sockaddr, len: ffi::socklen_t) -> Option<&mut Cached> {
+        let addr = unsafe { slice::from_raw_parts(sa.cast::<u8>(), len as usize) };
+
+        self.cache()
+            .iter()
+            .map(|q| unsafe { q.data::<Cached>(0).as_mut() })
+            .find(|item| item.socklen == len && item.addr() == addr)
+    }
+}
+
+impl Cached {
+    fn queue_mut(&mut self) -> &mut QueueRef {
+        unsafe { QueueRef::from_ptr_mut(&mut self.queue) }
+    }
+
+    fn addr(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                &self.sockaddr as *const _ as *const u8,
+                self.socklen as usize,
+            )
+        }
+    }
+
+    fn set_addr(&mut self, sa: *const ffi::sockaddr, len: ffi::socklen_t) {
+        unsafe {
+            ptr::copy_nonoverlapping(
+                sa.cast::<u8>(),
+                &mut self.sockaddr as *mut _ as *mut u8,
+                len as usize,
+            );
+        }
+
+        self.socklen = len;
+    }
+
+    /// Moves the item from the cache to the free list.
+    fn release(&mut self) {
+        let mut ka = self.keepalive;
+
+        self.queue_mut().remove();
+        unsafe { ka.as_mut() }
+            .free_mut()
+            .insert_head(self.queue_mut());
+    }
+}
+
+#[native_handler(name = ngx_rt_http_upstream_get_keepalive_peer)]
+fn get_keepalive_peer(pc: &mut PeerConnRef, peer: &mut Peer) -> Code {
+    let rc = unsafe { (peer.get.expect("get"))(pc.as_ptr(), peer.data) };
+
+    if rc != ffi::NGX_OK as ffi::ngx_int_t {
+        return rc.into();
+    }
+
+    let ka = unsafe { peer.keepalive.as_mut() };
+
+    if let Some(item) = ka.find(pc.sockaddr, pc.socklen) {
+        let c = item.connection;
+
+        item.release();
+
+        unsafe {
+            (*c).set_idle(0);
+            (*c).sent = 0;
+            (*c).data = ptr::null_mut();
+            (*c).log = pc.log;
+            (*(*c).read).log = pc.log;
+            (*(*c).write).log = pc.log;
+            (*(*c).pool).log = pc.log;
+
+            if (*(*c).read).timer_set() != 0 {
+                EventRef::from_ptr_mut((*c).read).del_timer();
+            }
+        }
+
+        pc.connection = c;
+        pc.set_cached(1);
+
+        return Code::DONE;
+    }
+
+    Code::OK
+}
+
+#[native_handler(name = ngx_rt_http_upstream_free_keepalive_peer)]
+fn free_keepalive_peer(pc: &mut PeerConnRef, peer: &mut Peer, state: usize) {
+    if can_keepalive(pc, peer, state) {
+        unsafe { cache_connection(pc, peer) };
+    }
+
+    unsafe { (peer.free.expect("free"))(pc.as_ptr(), peer.data, state) }
+}
+
+fn can_keepalive(pc: &PeerConnRef, peer: &Peer, state: usize) -> bool {
+    let Some(c) = pc.connection() else {
+        return false;
+    };
+
+    if PeerState::from_bits_truncate(state).contains(PeerState::FAILED)
+        || c.read().eof()
+        || c.read().error()
+        || c.read().timedout()
+        || c.write().error()
+        || c.write().timedout()
+    {
+        return false;
+    }
+
+    let ka = unsafe { peer.keepalive.as_ref() };
+    let u = unsafe { UpstreamRef::from_ptr(peer.upstream.as_ptr()) };
+    let start_time = unsafe { c.as_raw().start_time };
+
+    c.requests() < ka.requests
+        && (time::current().as_millis() as usize).wrapping_sub(start_time) <= *ka.time
+        && u.keepalive()
+        && u.request_body_sent()
+        && !u.upgrade()
+        && unsafe { ffi::ngx_terminate == 0 && ffi::ngx_exiting == 0 }
+        && unsafe { ffi::ngx_handle_read_event(c.as_raw().read, 0) } == ffi::NGX_OK as isize
+}
+
+unsafe fn cache_connection(pc: &mut PeerConnRef, peer: &mut Peer) {
+    let ka = peer.keepalive.as_mut();
+    let c = pc.connection;
+
+    let item = if let Some(q) = ka.free().head() {
+        q.data::<Cached>(0).as_mut()
+    } else if let Some(q) = ka.cache().last() {
+        let item = q.data::<Cached>(0).as_mut();
+
+        close(item.connection);
+        item
+    } else {
+        return;
+    };
+
+    item.queue_mut().remove();
+
+    ka.cache_mut().insert_head(item.queue_mut());
+
+    item.connection = c;
+    item.set_addr(pc.sockaddr, pc.socklen);
+
+    pc.connection = ptr::null_mut();
+
+    let rev = EventRef::from_ptr_mut((*c).read);
+    let wev = EventRef::from_ptr_mut((*c).write);
+
+    (*(*c).read).set_delayed(0);
+    rev.add_timer(ka.timeout.into());
+
+    if wev.timer_set() {
+        wev.del_timer();
+    }
+
+    (*(*c).write).handler = Some(ngx_rt_http_upstream_keepalive_dummy_handler);
+    (*(*c).read).handler = Some(ngx_rt_http_upstream_keepalive_close_handler);
+
+    (*c).data = item as *mut Cached as *mut _;
+    (*c).set_idle(1);
+    (*c).log = (*ffi::ngx_cycle).log;
+    (*(*c).read).log = (*ffi::ngx_cycle).log;
+    (*(*c).write).log = (*ffi::ngx_cycle).log;
+    (*(*c).pool).log = (*ffi::ngx_cycle).log;
+
+    if rev.ready() {
+        ngx_rt_http_upstream_keepalive_close_handler((*c).read);
+    }
+}
+
+#[native_handler(name = ngx_rt_http_upstream_keepalive_dummy_handler)]
+fn keepalive_dummy_handler(_ev: &mut EventRef) {}
+
+#[native_handler(name = ngx_rt_http_upstream_keepalive_close_handler)]
+fn keepalive_close_handler(ev: &mut EventRef) {
+    unsafe {
+        let c = ev.as_raw().data.cast::<ffi::ngx_connection_t>();
+
+        if (*c).close() == 0 && !ev.timedout() {
+            let mut buf = 0u8;
+            let n = libc::recv((*c).fd, &mut buf as *mut _ as *mut _, 1, libc::MSG_PEEK);
+
+            if n == -1 && errno::errno().0 == libc::EAGAIN {
+                ev.as_raw_mut().set_ready(0);
+
+                if ffi::ngx_handle_read_event(ev.as_ptr(), 0) == ffi::NGX_OK as isize {
+                    return;
+                }
+            }
+        }
+
+        let item = (*c).data.cast::<Cached>();
+
+        close(c);
+
+        if let Some(item) = item.as_mut() {
+            item.release();
+        }
+    }
+}
+
+#[native_handler(name = ngx_rt_http_upstream_keepalive_set_session)]
+fn keepalive_set_session(pc: &mut PeerConnRef, peer: &mut Peer) -> isize {
+    unsafe { (peer.set_session.expect("set_session"))(pc.as_ptr(), peer.data) }
+}
+
+#[native_handler(name = ngx_rt_http_upstream_keepalive_save_session)]
+fn keepalive_save_session(pc: &mut PeerConnRef, peer: &mut Peer) {
+    unsafe { (peer.save_session.expect("save_session"))(pc.as_ptr(), peer.data) }
+}
+
+unsafe extern "C" fn close(c: *mut ffi::ngx_connection_t) {
+    if let Some(ssl) = (*c).ssl.as_mut() {
+        ssl.set_no_wait_shutdown(1);
+        ssl.set_no_send_shutdown(1);
+
+        if ffi::ngx_ssl_shutdown(c) == ffi::NGX_AGAIN as isize {
+            ssl.handler = Some(close);
+            return;
+        }
+    }
+
+    ffi::ngx_destroy_pool((*c).pool);
+    ffi::ngx_close_connection(c);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::zeroed;
+
+    use crate::{
+        core::{Log, Pool},
+        event::timer,
+    };
+
+    use super::*;
+
+    static mut ADDR: ffi::sockaddr = unsafe { zeroed() };
+
+    unsafe extern "C" fn get_peer(pc: *mut ffi::ngx_peer_connection_t, _: *mut c_void) -> isize {
+        (*pc).sockaddr = ptr::addr_of_mut!(ADDR);
+        (*pc).socklen = mem::size_of::<ffi::sockaddr>() as _;
+
+        ffi::NGX_OK as isize
+    }
+
+    unsafe extern "C" fn free_peer(pc: *mut ffi::ngx_peer_connection_t, _: *mut c_void, _: usize) {
+        (*pc).tries -= 1;
+    }
+
+    #[test]
+    fn keepalive() {
+        let _lock = timer::tests::LOCK
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let p = Pool::new(4096, Log::stderr()).unwrap();
+        let ka = Keepalive::create(&p, 1).unwrap();
+
+        assert!(ka.is_empty());
+
+        unsafe {
+            let cycle: *mut ffi::ngx_cycle_t = Box::into_raw(Box::new(zeroed()));
+            let rev: *mut ffi::ngx_event_t = Box::into_raw(Box::new(zeroed()));
+            let wev: *mut ffi::ngx_event_t = Box::into_raw(Box::new(zeroed()));
+            let c: *mut ffi::ngx_connection_t = Box::into_raw(Box::new(zeroed()));
+            let u: *mut ffi::ngx_http_upstream_t = Box::into_raw(Box::new(zeroed()));
+            let log = Log::stderr().as_ptr();
+
+            (*cycle).log = log;
+            ffi::ngx_cycle = cycle;
+
+            (*c).read = rev;
+            (*c).write = wev;
+            (*c).log = log;
+            (*c).pool = p.as_ptr();
+            (*rev).data = c.cast();
+            (*rev).log = log;
+            (*wev).log = log;
+
+            (*u).set_keepalive(1);
+            (*u).set_request_body_sent(1);
+            (*u).peer.tries = 2;
+            (*u).peer.log = log;
+            (*u).peer.get = Some(get_peer);
+            (*u).peer.free = Some(free_peer);
+
+            ka.init_peer(&p, UpstreamRef::from_ptr_mut(u)).unwrap();
+
+            let pc = ptr::addr_of_mut!((*u).peer);
+            let (get, free) = ((*pc).get.unwrap(), (*pc).free.unwrap());
+
+            // no cached connection, the balancer picks a peer
+            assert_eq!(get(pc, (*pc).data), ffi::NGX_OK as isize);
+            assert!((*pc).connection.is_null());
+
+            // the idle connection is cached instead of closed
+            (*pc).connection = c;
+            free(pc, (*pc).data, PeerState::KEEPALIVE.bits());
+
+            assert_eq!(ka.len(), 1);
+            assert!((*pc).connection.is_null());
+            assert_eq!((*pc).tries, 1);
+            assert_eq!((*c).idle(), 1);
+            assert_eq!((*rev).timer_set(), 1);
+
+            // the cached connection is reused for the same peer
+            assert_eq!(get(pc, (*pc).data), ffi::NGX_DONE as isize);
+            assert_eq!((*pc).connection, c);
+            assert_eq!((*pc).cached(), 1);
+            assert_eq!((*c).idle(), 0);
+            assert_eq!((*rev).timer_set(), 0);
+            assert!(ka.is_empty());
+
+            // the failed connection is not cached
+            free(pc, (*pc).data, PeerState::FAILED.bits());
+
+            assert!(ka.is_empty());
+            assert_eq!((*pc).connection, c);
+            assert_eq!((*pc).tries, 0);
+
+            ffi::ngx_cycle = ptr::null_mut();
+
+            for p in [rev, wev] {
+                drop(Box::from_raw(p));
+            }
+            drop(Box::from_raw(c));
+            drop(Box::from_raw(u));
+            drop(Box::from_raw(cycle));
+        }
+    }
+}
@@ -1,10 +1,20 @@
 mod conf;
+mod headers_in;
+mod keepalive;
 mod module;
+mod next;
 mod peer;
+mod resolved;
+mod state;
 #[allow(clippy::module_inception)]
 mod upstream;
 
-pub use self::conf::{MainConf, MainConfRef, SrvConf, SrvConfRef};
+pub use self::conf::{MainConf, MainConfRef, SrvConf, SrvConfRef, UpstreamConf, UpstreamConfRef};
+pub use self::headers_in::{HeadersIn, HeadersInRef};
+pub use self::keepalive::Keepalive;
 pub use self::module::{main_conf, main_conf_mut, module, srv_conf, srv_conf_mut};
+pub use self::next::Failure;
 pub use self::peer::{InitFn, InitPeerFn, Peer, PeerRef};
+pub use self::resolved::{Resolved, ResolvedRef};
+pub use self::state::{State, StateRef};
 pub use self::upstream::{Upstream, UpstreamRef};
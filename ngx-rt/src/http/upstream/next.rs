@@ -0,0 +1,246 @@
+use bitflags::bitflags;
+
+use crate::{
+    core::time,
+    ffi,
+    http::{Method, RequestRef},
+};
+
+use super::UpstreamRef;
+
+bitflags! {
+    /// The type of failure of an upstream attempt, as used by `proxy_next_upstream`.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Failure: u32 {
+        const ERROR = ffi::NGX_HTTP_UPSTREAM_FT_ERROR;
+        const TIMEOUT = ffi::NGX_HTTP_UPSTREAM_FT_TIMEOUT;
+        const INVALID_HEADER = ffi::NGX_HTTP_UPSTREAM_FT_INVALID_HEADER;
+        const HTTP_500 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_500;
+        const HTTP_502 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_502;
+        const HTTP_503 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_503;
+        const HTTP_504 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_504;
+        const HTTP_403 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_403;
+        const HTTP_404 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_404;
+        const HTTP_429 = ffi::NGX_HTTP_UPSTREAM_FT_HTTP_429;
+        const UPDATING = ffi::NGX_HTTP_UPSTREAM_FT_UPDATING;
+        const BUSY_LOCK = ffi::NGX_HTTP_UPSTREAM_FT_BUSY_LOCK;
+        const MAX_WAITING = ffi::NGX_HTTP_UPSTREAM_FT_MAX_WAITING;
+        /// The request was already sent with a non-idempotent method.
+        const NON_IDEMPOTENT = ffi::NGX_HTTP_UPSTREAM_FT_NON_IDEMPOTENT;
+        const NOLIVE = ffi::NGX_HTTP_UPSTREAM_FT_NOLIVE;
+        const OFF = ffi::NGX_HTTP_UPSTREAM_FT_OFF;
+
+        /// All failures caused by the status code of the upstream response.
+        const STATUS = ffi::NGX_HTTP_UPSTREAM_FT_STATUS;
+    }
+}
+
+impl Failure {
+    /// Returns the failure type matching the status code of an upstream response.
+    pub fn from_status(status: usize) -> Option<Self> {
+        match status as u32 {
+            ffi::NGX_HTTP_INTERNAL_SERVER_ERROR => Some(Self::HTTP_500),
+            ffi::NGX_HTTP_BAD_GATEWAY => Some(Self::HTTP_502),
+            ffi::NGX_HTTP_SERVICE_UNAVAILABLE => Some(Self::HTTP_503),
+            ffi::NGX_HTTP_GATEWAY_TIME_OUT => Some(Self::HTTP_504),
+            ffi::NGX_HTTP_FORBIDDEN => Some(Self::HTTP_403),
+            ffi::NGX_HTTP_NOT_FOUND => Some(Self::HTTP_404),
+            ffi::NGX_HTTP_TOO_MANY_REQUESTS => Some(Self::HTTP_429),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if requests with the `method` must not be retried
+    /// once they were sent to the upstream server.
+    pub fn is_non_idempotent(method: Method) -> bool {
+        method.intersects(Method::POST | Method::LOCK | Method::PATCH)
+    }
+}
+
+impl UpstreamRef {
+    /// Returns the type of failure of the current upstream attempt.
+    ///
+    /// nginx does not pass the failure type to the balancer, so it is derived
+    /// in the same way as nginx detects it before calling `ngx_http_upstream_next()`:
+    /// the timed out upstream connection, then the failed or prematurely closed connection,
+    /// otherwise the status of the upstream response if `bad_status` is reported by the caller,
+    /// e.g. the status was tested against `*_next_upstream`.
+    ///
+    /// An invalid response header can't be told apart from an error,
+    /// and is reported as [`Failure::ERROR`].
+    pub fn failure(&self, bad_status: bool) -> Failure {
+        if let Some(c) = self.peer().connection() {
+            let (rev, wev) = (c.read(), c.write());
+
+            if c.timedout() || rev.timedout() || wev.timedout() {
+                return Failure::TIMEOUT;
+            }
+
+            if c.error() || rev.error() || wev.error() || rev.eof() {
+                return Failure::ERROR;
+            }
+        }
+
+        if bad_status {
+            if let Some(failure) = Failure::from_status(self.headers_in().status_n()) {
+                return failure;
+            }
+        }
+
+        Failure::ERROR
+    }
+
+    /// Returns `true` if nginx will pass the request to the next peer
+    /// after the `failure`, according to the `*_next_upstream` directives.
+    ///
+    /// It mirrors the conditions of `ngx_http_upstream_next()`.
+    pub fn next_upstream(&self, r: &RequestRef, failure: Failure) -> bool {
+        let Some(conf) = self.conf() else {
+            return false;
+        };
+
+        if r.connection().error() {
+            return false;
+        }
+
+        // nginx retries the cached connection without counting it as a try.
+        let tries = if self.peer().cached() && failure == Failure::ERROR {
+            self.peer().tries() + 1
+        } else {
+            self.peer().tries()
+        };
+
+        let mut failure = failure;
+
+        if self.request_sent() && Failure::is_non_idempotent(r.method()) {
+            failure |= Failure::NON_IDEMPOTENT;
+        }
+
+        if tries == 0
+            || !conf.next_upstream().contains(failure)
+            || (self.request_sent() && r.request_body_no_buffering())
+        {
+            return false;
+        }
+
+        let timeout = conf.next_upstream_timeout();
+
+        *timeout == 0
+            || (time::current().as_millis() as usize).wrapping_sub(*self.peer().start_time())
+                < *timeout
+    }
+
+    /// Applies a retry `policy` to the failed upstream attempt of the request.
+    ///
+    /// It should be called from a [`FreePeerFn`](crate::event::FreePeerFn)
+    /// after the attempt is marked as failed, the further retries are disabled
+    /// if the `policy` returns `false`, see [`failure`](Self::failure) for `bad_status`.
+    pub fn retry_with<F>(&mut self, r: &RequestRef, bad_status: bool, policy: F) -> bool
+    where
+        F: FnOnce(&Self, Failure) -> bool,
+    {
+        let failure = self.failure(bad_status);
+        let retry = self.next_upstream(r, failure) && policy(self, failure);
+
+        if !retry {
+            self.peer_mut().set_tries(0);
+        }
+
+        retry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::zeroed;
+
+    use foreign_types::ForeignTypeRef;
+
+    use super::*;
+
+    #[test]
+    fn from_status() {
+        assert_eq!(Failure::from_status(502), Some(Failure::HTTP_502));
+        assert_eq!(Failure::from_status(404), Some(Failure::HTTP_404));
+        assert_eq!(Failure::from_status(200), None);
+        assert_eq!(Failure::from_status(0), None);
+    }
+
+    #[test]
+    fn next_upstream() {
+        unsafe {
+            let c: *mut ffi::ngx_connection_t = Box::into_raw(Box::new(zeroed()));
+            let conf: *mut ffi::ngx_http_upstream_conf_t = Box::into_raw(Box::new(zeroed()));
+            let u: *mut ffi::ngx_http_upstream_t = Box::into_raw(Box::new(zeroed()));
+            let r: *mut ffi::ngx_http_request_t = Box::into_raw(Box::new(zeroed()));
+
+            (*conf).next_upstream = (Failure::ERROR | Failure::TIMEOUT).bits() as _;
+            (*u).conf = conf;
+            (*u).peer.tries = 1;
+            (*r).connection = c;
+            (*r).method = ffi::NGX_HTTP_POST as _;
+
+            let req = RequestRef::from_ptr(r);
+            let up = UpstreamRef::from_ptr(u);
+
+            assert_eq!(up.failure(false), Failure::ERROR);
+            assert!(up.next_upstream(req, Failure::ERROR));
+            assert!(up.next_upstream(req, Failure::TIMEOUT));
+            assert!(!up.next_upstream(req, Failure::HTTP_502));
+
+            // the request with a non-idempotent method was sent
+            (*u).set_request_sent(1);
+            assert!(!up.next_upstream(req, Failure::ERROR));
+
+            (*conf).next_upstream |= Failure::NON_IDEMPOTENT.bits() as ffi::ngx_uint_t;
+            assert!(up.next_upstream(req, Failure::ERROR));
+
+            // the unbuffered request body was consumed
+            (*r).set_request_body_no_buffering(1);
+            assert!(!up.next_upstream(req, Failure::ERROR));
+
+            (*r).set_request_body_no_buffering(0);
+            (*u).peer.tries = 0;
+            assert!(!up.next_upstream(req, Failure::ERROR));
+
+            // the cached connection doesn't count as a try
+            (*u).peer.set_cached(1);
+            assert!(up.next_upstream(req, Failure::ERROR));
+            assert!(!up.next_upstream(req, Failure::TIMEOUT));
+
+            // the client closed the connection
+            (*u).peer.tries = 1;
+            (*c).set_error(1);
+            assert!(!up.next_upstream(req, Failure::ERROR));
+
+            (*u).headers_in.status_n = 502;
+            assert_eq!(up.failure(false), Failure::ERROR);
+            assert_eq!(up.failure(true), Failure::HTTP_502);
+
+            // the connection was reset after the status line was parsed
+            let pc: *mut ffi::ngx_connection_t = Box::into_raw(Box::new(zeroed()));
+            let rev: *mut ffi::ngx_event_t = Box::into_raw(Box::new(zeroed()));
+            let wev: *mut ffi::ngx_event_t = Box::into_raw(Box::new(zeroed()));
+
+            (*pc).read = rev;
+            (*pc).write = wev;
+            (*u).peer.connection = pc;
+            (*u).headers_in.status_n = 200;
+            (*rev).set_error(1);
+            assert_eq!(up.failure(true), Failure::ERROR);
+
+            (*rev).set_timedout(1);
+            assert_eq!(up.failure(true), Failure::TIMEOUT);
+
+            drop(Box::from_raw(wev));
+            drop(Box::from_raw(rev));
+            drop(Box::from_raw(pc));
+
+            drop(Box::from_raw(r));
+            drop(Box::from_raw(u));
+            drop(Box::from_raw(conf));
+            drop(Box::from_raw(c));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::{net::SocketAddr, ptr::NonNull};
+
+use foreign_types::foreign_type;
+
+use crate::{
+    core::{alloc_sockaddr, sockaddr, PoolRef, Str},
+    ffi, never_drop, AsRawMut, AsRawRef, Error,
+};
+
+foreign_type! {
+    pub unsafe type Resolved: Send {
+        type CType = ffi::ngx_http_upstream_resolved_t;
+
+        fn drop = never_drop::<ffi::ngx_http_upstream_resolved_t>;
+    }
+}
+
+impl ResolvedRef {
+    str! {
+        /// The host name of the upstream server.
+        &mut host;
+
+        /// The name of the resolved address.
+        &mut name;
+    }
+
+    property! {
+        /// The port of the upstream server.
+        port: u16 { get; set; };
+
+        /// The number of resolved addresses.
+        naddrs: usize;
+    }
+
+    /// The upstream target was specified without a port.
+    pub fn no_port(&self) -> bool {
+        unsafe { self.as_raw().no_port != 0 }
+    }
+
+    /// The address of the upstream server, if it was specified directly.
+    pub fn addr(&self) -> Option<SocketAddr> {
+        unsafe {
+            let r = self.as_raw();
+
+            NonNull::new(r.sockaddr).and_then(|p| sockaddr(p, r.socklen as usize))
+        }
+    }
+
+    /// Sets the address of the upstream server.
+    ///
+    /// The address is allocated from the `pool` and used as the only peer,
+    /// the resolver will not be used for the upstream request.
+    pub fn set_addr(&mut self, pool: &PoolRef, addr: SocketAddr) -> Result<&mut Self, Error> {
+        let host = pool
+            .strdup(addr.ip().to_string())
+            .ok_or(Error::OutOfMemory)?;
+        let (sa, len) = alloc_sockaddr(pool, addr).ok_or(Error::OutOfMemory)?;
+
+        unsafe {
+            let r = self.as_raw_mut();
+
+            r.host = host.into();
+            r.port = addr.port();
+            r.no_port = 0;
+            r.naddrs = 1;
+            r.sockaddr = sa;
+            r.socklen = len as ffi::socklen_t;
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the host name of the upstream server to be resolved.
+    pub fn set_host(&mut self, host: Str, port: Option<u16>) -> &mut Self {
+        unsafe {
+            let r = self.as_raw_mut();
+
+            r.host = host.into();
+            r.port = port.unwrap_or_default();
+            r.no_port = if port.is_some() { 0 } else { 1 };
+            r.naddrs = 0;
+            r.sockaddr = std::ptr::null_mut();
+            r.socklen = 0;
+        }
+
+        self
+    }
+}
@@ -0,0 +1,48 @@
+use foreign_types::foreign_type;
+
+use crate::{core::MSec, ffi, never_drop};
+
+foreign_type! {
+    pub unsafe type State: Send {
+        type CType = ffi::ngx_http_upstream_state_t;
+
+        fn drop = never_drop::<ffi::ngx_http_upstream_state_t>;
+    }
+}
+
+impl StateRef {
+    property! {
+        /// The status code of the upstream response.
+        status: usize;
+
+        /// Time spent on receiving the response from the upstream server.
+        response_time into MSec;
+
+        /// Time spent on establishing a connection with the upstream server.
+        ///
+        /// It is unset if the connection has not been established yet.
+        connect_time into MSec;
+
+        /// Time spent on receiving the response header from the upstream server.
+        ///
+        /// It is unset if the header has not been received yet.
+        header_time into MSec;
+
+        /// Time the request spent in the upstream queue.
+        queue_time into MSec;
+
+        /// The length of the response obtained from the upstream server.
+        response_length: i64;
+
+        /// The number of bytes received from the upstream server.
+        bytes_received: i64;
+
+        /// The number of bytes sent to the upstream server.
+        bytes_sent: i64;
+    }
+
+    str! {
+        /// The name of the upstream peer.
+        peer?;
+    }
+}
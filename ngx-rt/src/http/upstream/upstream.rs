@@ -1,6 +1,12 @@
 use foreign_types::foreign_type;
 
-use crate::{event::PeerConnRef, ffi, never_drop};
+use crate::{
+    core::{BufRef, MSec},
+    event::PeerConnRef,
+    ffi, never_drop,
+};
+
+use super::{HeadersInRef, ResolvedRef, SrvConfRef, StateRef, UpstreamConfRef};
 
 foreign_type! {
     pub unsafe type Upstream: Send {
@@ -11,5 +17,51 @@ foreign_type! {
 }
 
 impl UpstreamRef {
-    property!(&mut peer: &mut PeerConnRef);
+    property! {
+        &mut peer: &mut PeerConnRef;
+
+        /// The headers of the upstream response.
+        &mut headers_in: &mut HeadersInRef;
+
+        /// The buffer for reading the upstream response.
+        &mut buffer: &mut BufRef;
+
+        /// The state of the current upstream attempt.
+        state as &mut StateRef;
+
+        /// The upstream target resolved for this request.
+        ///
+        /// It is set when the upstream server is specified with variables,
+        /// and can be overridden to redirect the request to another target.
+        resolved as &mut ResolvedRef;
+
+        /// The upstream configuration used by this request.
+        upstream as &SrvConfRef;
+
+        /// The configuration of the module passing the request upstream.
+        conf as &UpstreamConfRef;
+
+        /// The time when the upstream request was started.
+        start_time into MSec;
+    }
+
+    str! {
+        &method;
+        &schema;
+        &uri;
+    }
+
+    flag! {
+        store;
+        cacheable;
+        accel;
+        ssl;
+        buffering;
+        keepalive;
+        upgrade;
+        error;
+        request_sent;
+        request_body_sent;
+        header_sent;
+    }
 }
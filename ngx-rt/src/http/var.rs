@@ -1,6 +1,6 @@
 use std::{
     ops::{Deref, DerefMut},
-    ptr::{null_mut, NonNull},
+    ptr::{self, NonNull},
     slice,
 };
 
@@ -12,7 +12,9 @@ use crate::{
     ffi, native_callback, never_drop, property, AsRawMut, AsRawRef, Error,
 };
 
-use super::RequestRef;
+use super::{core, RequestRef};
+
+pub use crate::core::{Value, ValueRef};
 
 pub type RawVar = ffi::ngx_http_variable_t;
 
@@ -54,21 +56,21 @@ macro_rules! ngx_var {
         var
     }};
     ( __set $var:ident => ) => {};
-    ( __set $var:ident => get = $fn:ident ) => {
+    ( __set $var:ident => get = $fn:path ) => {
         $var.get_handler = Some($fn);
     };
-    ( __set $var:ident => get = $fn:ident , $( $tt:tt )* ) => {
+    ( __set $var:ident => get = $fn:path , $( $tt:tt )* ) => {
         $var.get_handler = Some($fn);
 
-        $crate::ngx_var!( __set $var => $( $tt:tt )*);
+        $crate::ngx_var!( __set $var => $( $tt )*);
     };
-    ( __set $var:ident => set = $fn:ident ) => {
+    ( __set $var:ident => set = $fn:path ) => {
         $var.set_handler = Some($fn);
     };
-    ( __set $var:ident => set = $fn:ident , $( $tt:tt )* ) => {
+    ( __set $var:ident => set = $fn:path , $( $tt:tt )* ) => {
         $var.set_handler = Some($fn);
 
-        $crate::ngx_var!( __set $var => $( $tt:tt )*);
+        $crate::ngx_var!( __set $var => $( $tt )*);
     };
     ( __set $var:ident => data = $data:expr ) => {
         $var.data = $data;
@@ -76,7 +78,7 @@ macro_rules! ngx_var {
     ( __set $var:ident => data = $data:expr , $( $tt:tt )* ) => {
         $var.data = $data;
 
-        $crate::ngx_var!( __set $var => $( $tt:tt )* );
+        $crate::ngx_var!( __set $var => $( $tt )* );
     };
     ( __set $var:ident => flags = $flags:expr ) => {
         $var.flags = $flags;
@@ -84,7 +86,7 @@ macro_rules! ngx_var {
     ( __set $var:ident => flags = $flags:expr , $( $tt:tt )* ) => {
         $var.flags = $flags;
 
-        $crate::ngx_var!( __set $var => $( $tt:tt )* );
+        $crate::ngx_var!( __set $var => $( $tt )* );
     };
     ( __set $var:ident => index = $index:expr ) => {
         $var.index = $index;
@@ -92,7 +94,7 @@ macro_rules! ngx_var {
     ( __set $var:ident => index = $index:expr , $( $tt:tt )* ) => {
         $var.index = $index;
 
-        $crate::ngx_var!( __set $var => $( $tt:tt )* );
+        $crate::ngx_var!( __set $var => $( $tt )* );
     };
 }
 
@@ -140,6 +142,29 @@ impl ConfRef {
             }
         }
     }
+
+    /// Returns the typed index of the variable, the variable becomes indexed.
+    pub fn variable_index<S: AsRef<str>>(&self, name: S) -> Option<VariableIndex> {
+        self.get_variable_index(name).map(VariableIndex)
+    }
+}
+
+/// A typed index of the indexed variable,
+/// obtained at configuration time with [`ConfRef::variable_index`].
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VariableIndex(usize);
+
+impl VariableIndex {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<VariableIndex> for usize {
+    fn from(idx: VariableIndex) -> Self {
+        idx.0
+    }
 }
 
 impl RequestRef {
@@ -188,6 +213,119 @@ impl RequestRef {
             .map(|p| ValueRef::from_ptr_mut(p.as_ptr()))
         }
     }
+
+    /// Get a cached value of the indexed variable.
+    pub fn variable(&self, idx: VariableIndex) -> Option<&mut ValueRef> {
+        self.get_indexed_variable(idx.0)
+    }
+
+    /// Get value of the indexed variable and flushes the cache for non-cacheable variables.
+    pub fn flushed_variable(&self, idx: VariableIndex) -> Option<&mut ValueRef> {
+        self.get_flushed_variable(idx.0)
+    }
+
+    /// Set value of the indexed variable.
+    ///
+    /// The value is copied to the request pool, and passed to the `set_handler` of the variable,
+    /// or stored in the request like the `set` directive does.
+    ///
+    /// Returns [`Code::DECLINED`] if the variable is not changeable.
+    pub fn set_variable<V: AsRef<[u8]>>(&self, idx: VariableIndex, value: V) -> Result<(), Code> {
+        let indexed = self.variables().get(idx.0).ok_or(Code::DECLINED)?;
+
+        // the indexed variables don't have the `set_handler` of the defined variables
+        let name = unsafe { slice::from_raw_parts(indexed.name.data, indexed.name.len) };
+        let var = self.find_variable(name).unwrap_or(indexed);
+
+        self.set_variable_value(var, idx.0, value.as_ref())
+    }
+
+    /// Set value of variable by name.
+    ///
+    /// The variable must be changeable, and have a `set_handler` or be indexed,
+    /// e.g. defined by the `set` directive.
+    pub fn set_variable_by_name<S, V>(&self, name: S, value: V) -> Result<(), Code>
+    where
+        S: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        let var = self
+            .find_variable(name.as_ref().as_bytes())
+            .ok_or(Code::DECLINED)?;
+
+        if var.set_handler.is_none() && var.flags & ffi::NGX_HTTP_VAR_INDEXED as usize == 0 {
+            return Err(Code::DECLINED);
+        }
+
+        self.set_variable_value(var, var.index, value.as_ref())
+    }
+
+    fn variables(&self) -> &[RawVar] {
+        core::main_conf(self).map_or(&[], |cmcf| unsafe {
+            let a = &cmcf.as_raw().variables;
+
+            slice::from_raw_parts(a.elts.cast(), a.nelts)
+        })
+    }
+
+    /// Find the defined variable by name,
+    /// in the `variables_keys` while parsing the configuration, or in the `variables_hash` after.
+    fn find_variable(&self, name: &[u8]) -> Option<&RawVar> {
+        let cmcf = unsafe { core::main_conf(self)?.as_raw() };
+        let mut name = name.to_vec();
+        let key = hash::strlow_in_place(&mut name);
+
+        unsafe {
+            if let Some(keys) = cmcf.variables_keys.as_ref() {
+                slice::from_raw_parts(
+                    keys.keys.elts.cast::<ffi::ngx_hash_key_t>(),
+                    keys.keys.nelts,
+                )
+                .iter()
+                .find(|k| slice::from_raw_parts(k.key.data, k.key.len) == name.as_slice())
+                .and_then(|k| k.value.cast::<RawVar>().as_ref())
+            } else if cmcf.variables_hash.buckets.is_null() {
+                None
+            } else {
+                hash::HashRef::<RawVar>::from_ptr(&cmcf.variables_hash as *const _ as *mut _)
+                    .find(key, std::str::from_utf8(&name).ok()?)
+                    .map(|var| var.as_ref())
+            }
+        }
+    }
+
+    fn set_variable_value(&self, var: &RawVar, idx: usize, value: &[u8]) -> Result<(), Code> {
+        if var.flags & ffi::NGX_HTTP_VAR_CHANGEABLE as usize == 0 {
+            return Err(Code::DECLINED);
+        }
+
+        unsafe {
+            let pool = self.pool();
+            let data = pool.pnalloc(value.len()).cast::<u8>();
+
+            if data.is_null() {
+                return Err(Code::ERROR);
+            }
+
+            ptr::copy_nonoverlapping(value.as_ptr(), data, value.len());
+
+            let value = slice::from_raw_parts(data, value.len());
+
+            if let Some(set) = var.set_handler {
+                let v = pool
+                    .calloc::<ffi::ngx_variable_value_t>()
+                    .ok_or(Code::ERROR)?;
+
+                ValueRef::from_ptr_mut(v).set_value(value);
+
+                set(self.as_ptr(), v, var.data);
+            } else {
+                ValueRef::from_ptr_mut(self.as_raw().variables.add(idx)).set_value(value);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Deref for VarRef {
@@ -248,28 +386,6 @@ bitflags! {
     }
 }
 
-foreign_type! {
-    pub unsafe type Value: Send {
-        type CType = ffi::ngx_variable_value_t;
-
-        fn drop = never_drop::<ffi::ngx_variable_value_t>;
-    }
-}
-
-impl Deref for ValueRef {
-    type Target = [u8];
-
-    fn deref(&self) -> &Self::Target {
-        self.as_bytes()
-    }
-}
-
-impl DerefMut for ValueRef {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.as_bytes_mut()
-    }
-}
-
 pub fn null_value() -> &'static ValueRef {
     unsafe { ValueRef::from_ptr(&ffi::ngx_http_variable_null_value as *const _ as *mut _) }
 }
@@ -278,66 +394,142 @@ pub fn true_value() -> &'static ValueRef {
     unsafe { ValueRef::from_ptr(&ffi::ngx_http_variable_true_value as *const _ as *mut _) }
 }
 
-impl ValueRef {
-    property! {
-        /// The length of the value
-        len(): u32 { get; set; }
-    }
-
-    flag! {
-        /// The value is valid
-        valid { get; set; };
+#[cfg(test)]
+mod tests {
+    use std::{ffi::c_void, mem::zeroed, sync::Mutex};
 
-        /// Do not cache result
-        no_cacheable { get; set; };
+    use crate::core::{Log, Pool};
 
-        /// The variable was not found
-        not_found { get; set; };
+    use super::*;
 
-        /// Used internally by the logging module to mark values that require escaping on output.
-        escape { get; set; };
+    unsafe extern "C" fn get(
+        _r: *mut ffi::ngx_http_request_t,
+        _v: *mut ffi::ngx_http_variable_value_t,
+        _data: usize,
+    ) -> ffi::ngx_int_t {
+        ffi::NGX_OK as ffi::ngx_int_t
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    unsafe extern "C" fn set(
+        _r: *mut ffi::ngx_http_request_t,
+        _v: *mut ffi::ngx_http_variable_value_t,
+        _data: usize,
+    ) {
     }
 
-    pub fn set_value<S>(&mut self, s: S) -> &mut Self
-    where
-        S: Into<Str>,
-    {
-        let s: Str = s.into();
-
-        self.set_data(NonNull::new(s.as_ptr()))
-            .set_len(s.len() as u32)
-            .set_valid(true)
-            .set_no_cacheable(false)
-            .set_not_found(false)
-    }
-
-    pub fn data<T>(&self) -> Option<NonNull<T>> {
-        NonNull::new(unsafe { self.as_raw().data.cast() })
+    #[test]
+    fn ngx_var() {
+        let var = ngx_var!(
+            "foo",
+            get = get,
+            set = self::set,
+            data = 1,
+            flags = Flags::NO_CACHEABLE.bits() as usize
+        );
+
+        assert!(var.get_handler.is_some());
+        assert!(var.set_handler.is_some());
+        assert_eq!(var.data, 1);
+        assert_eq!(var.flags, ffi::NGX_HTTP_VAR_NOCACHEABLE as usize);
     }
 
-    pub fn set_data<T>(&mut self, data: Option<NonNull<T>>) -> &mut Self {
-        unsafe { self.as_raw_mut().data = data.map_or_else(null_mut, |p| p.as_ptr().cast()) };
+    static FOO: Mutex<Vec<u8>> = Mutex::new(Vec::new());
 
-        self
+    unsafe extern "C" fn set_foo(
+        _r: *mut ffi::ngx_http_request_t,
+        v: *mut ffi::ngx_http_variable_value_t,
+        _data: usize,
+    ) {
+        *FOO.lock().unwrap() = ValueRef::from_ptr(v).to_vec();
     }
 
-    pub fn as_bytes(&self) -> &[u8] {
-        unsafe {
-            let r = self.as_raw();
+    unsafe fn array<T>(elts: &mut [T]) -> ffi::ngx_array_t {
+        let mut a: ffi::ngx_array_t = zeroed();
 
-            slice::from_raw_parts(r.data as *const _, r.len() as usize)
-        }
+        a.elts = elts.as_mut_ptr().cast();
+        a.nelts = elts.len();
+        a.nalloc = elts.len();
+        a.size = std::mem::size_of::<T>();
+        a
     }
 
-    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
-        unsafe {
-            let r = self.as_raw();
+    #[test]
+    fn set_variable() {
+        let pool = Pool::new(4096, Log::stderr()).unwrap();
 
-            slice::from_raw_parts_mut(r.data, r.len() as usize)
+        unsafe {
+            // defined by `ngx_http_add_variable()`
+            let defined = Box::into_raw(Box::new([
+                ngx_var!(
+                    "foo",
+                    set = set_foo,
+                    flags = Flags::CHANGEABLE.bits() as usize
+                ),
+                ngx_var!(
+                    "bar",
+                    flags = (Flags::CHANGEABLE | Flags::INDEXED).bits() as usize
+                ),
+                ngx_var!("ro", flags = Flags::INDEXED.bits() as usize),
+            ]));
+
+            // indexed by `ngx_http_get_variable_index()`, without the `set_handler`
+            let indexed = Box::into_raw(Box::new([
+                ngx_var!("foo", flags = Flags::CHANGEABLE.bits() as usize),
+                ngx_var!("bar", flags = Flags::CHANGEABLE.bits() as usize),
+                ngx_var!("ro"),
+            ]));
+
+            let keys = Box::into_raw(Box::new(
+                (*defined)
+                    .iter_mut()
+                    .map(|var| {
+                        let mut key: ffi::ngx_hash_key_t = zeroed();
+
+                        key.key = var.name;
+                        key.value = (var as *mut RawVar).cast();
+                        key
+                    })
+                    .collect::<Vec<_>>(),
+            ));
+            let variables_keys: *mut ffi::ngx_hash_keys_arrays_t =
+                Box::into_raw(Box::new(zeroed()));
+
+            (*variables_keys).keys = array(&mut *keys);
+
+            let cmcf: *mut ffi::ngx_http_core_main_conf_t = Box::into_raw(Box::new(zeroed()));
+
+            (*cmcf).variables = array(&mut *indexed);
+            (*cmcf).variables_keys = variables_keys;
+
+            let main_conf = Box::into_raw(Box::new([cmcf.cast::<c_void>()]));
+            let values: *mut [ffi::ngx_variable_value_t; 3] = Box::into_raw(Box::new(zeroed()));
+            let r: *mut ffi::ngx_http_request_t = Box::into_raw(Box::new(zeroed()));
+
+            (*r).pool = pool.as_ptr();
+            (*r).main_conf = (*main_conf).as_mut_ptr();
+            (*r).variables = (*values).as_mut_ptr();
+            ffi::ngx_http_core_module.ctx_index = 0;
+
+            let req = RequestRef::from_ptr(r);
+
+            req.set_variable(VariableIndex(0), "x").unwrap();
+            assert_eq!(*FOO.lock().unwrap(), b"x", "should call the `set_handler`");
+            assert!(!ValueRef::from_ptr(&mut (*values)[0]).valid());
+
+            req.set_variable(VariableIndex(1), "y").unwrap();
+            assert_eq!(ValueRef::from_ptr(&mut (*values)[1]).as_bytes(), b"y");
+
+            assert_eq!(req.set_variable(VariableIndex(2), "z"), Err(Code::DECLINED));
+            assert_eq!(req.set_variable(VariableIndex(3), "z"), Err(Code::DECLINED));
+
+            req.set_variable_by_name("BAR", "z").unwrap();
+            assert_eq!(ValueRef::from_ptr(&mut (*values)[1]).as_bytes(), b"z");
+
+            assert_eq!(req.set_variable_by_name("ro", "z"), Err(Code::DECLINED));
+            assert_eq!(
+                req.set_variable_by_name("unknown", "z"),
+                Err(Code::DECLINED)
+            );
         }
     }
 }
@@ -18,6 +18,8 @@ mod raw;
 
 #[cfg(feature = "http")]
 pub mod http;
+#[cfg(feature = "mail")]
+pub mod mail;
 #[cfg(feature = "stream")]
 pub mod stream;
 
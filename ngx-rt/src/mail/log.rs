@@ -0,0 +1,38 @@
+macro_rules! define_mail_logger {
+    ( $( $name:ident => $level:ident ,)* ) => {
+        define_mail_logger! { __impl =>
+            ($d:tt) => {
+                ::paste::paste! {
+                    $(
+                        #[macro_export]
+                        macro_rules! [< mail_ $name >] {
+                            ($d log:expr, $d( $d args:tt )*) => {
+                                {
+                                    let log = ::std::convert::AsRef::<$d crate::core::LogRef>::as_ref($d log).mail();
+
+                                    $d crate::core::Logger::core(& log, $crate::core::LogLevel::$level, format!($d ($d args)*));
+                                }
+                            };
+                        }
+                    )*
+                }
+            }
+        }
+    };
+    ( __impl => $($body:tt)* ) => {
+        macro_rules! __with_dollar_sign { $($body)* }
+        __with_dollar_sign!($);
+    }
+}
+
+define_mail_logger! {
+    stderr => StdErr,
+    emerg => Emerg,
+    alert => Alert,
+    critical => Critical,
+    error => Error,
+    warn => Warn,
+    notice => Notice,
+    info => Info,
+    debug => Debug,
+}
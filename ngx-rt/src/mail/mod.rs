@@ -0,0 +1,14 @@
+mod conf;
+#[macro_use]
+mod log;
+mod module;
+mod session;
+
+pub use self::conf::{
+    Context as ConfContext, ContextRef as ConfContextRef, MainConf, SrvConf, UnsafeMainConf,
+    UnsafeSrvConf,
+};
+pub use self::module::{conf_ctx, main_conf, module};
+pub use self::session::{
+    AuthMethod, ModuleContext, Protocol, Session, SessionRef, UnsafeModuleContext,
+};
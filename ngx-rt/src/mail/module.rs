@@ -0,0 +1,25 @@
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::{ConfContext, CycleRef, ModuleRef},
+    ffi,
+};
+
+use super::{ConfContextRef, MainConf};
+
+extern "C" {
+    // `ngx_mail_module` is not declared in the `ngx_mail.h` header.
+    static mut ngx_mail_module: ffi::ngx_module_t;
+}
+
+pub fn module() -> &'static ModuleRef {
+    unsafe { ModuleRef::from_ptr(&mut ngx_mail_module as *mut _) }
+}
+
+pub fn conf_ctx(cycle: &CycleRef) -> Option<&ConfContextRef> {
+    cycle.conf_ctx(module())
+}
+
+pub fn main_conf<'a, T>(cycle: &'a CycleRef, m: &ModuleRef) -> Option<&'a T> {
+    conf_ctx(cycle).and_then(|ctx| ctx.main_conf(m))
+}
@@ -0,0 +1,219 @@
+use std::{net::SocketAddr, ptr::NonNull};
+
+use foreign_types::{foreign_type, ForeignTypeRef};
+use num_enum::FromPrimitive;
+
+use crate::{
+    core::{alloc_sockaddr, BufRef, ConnRef, LogRef, ModuleRef},
+    ffi, never_drop, AsRawRef, Error,
+};
+
+use super::{UnsafeMainConf, UnsafeSrvConf};
+
+foreign_type! {
+    pub unsafe type Session: Send {
+        type CType = ffi::ngx_mail_session_t;
+
+        fn drop = never_drop::<ffi::ngx_mail_session_t>;
+    }
+}
+
+impl UnsafeMainConf for SessionRef {
+    unsafe fn unchecked_main_conf<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().main_conf.add(idx).read().cast())
+    }
+}
+
+impl UnsafeSrvConf for SessionRef {
+    unsafe fn unchecked_srv_conf<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().srv_conf.add(idx).read().cast())
+    }
+}
+
+impl SessionRef {
+    property! {
+        /// client connection
+        connection: &ConnRef;
+
+        /// Buffer into which the client commands are read.
+        buffer as &mut BufRef;
+
+        /// The state of the protocol handler.
+        mail_state: usize;
+
+        /// The number of invalid commands received from the client.
+        errors: usize;
+
+        /// The number of authentication attempts.
+        login_attempt: usize;
+
+        /// The last command received from the client.
+        command: usize;
+    }
+
+    str! {
+        /// The user name used for authentication.
+        &mut login;
+
+        /// The password used for authentication.
+        &mut passwd;
+
+        /// The salt sent to the client for APOP or CRAM-MD5 authentication.
+        &salt;
+
+        /// The host name of the client from the reverse DNS lookup.
+        &host;
+
+        /// The argument of the SMTP `HELO` or `EHLO` command.
+        &smtp_helo;
+
+        /// The argument of the SMTP `MAIL FROM` command.
+        &smtp_from;
+
+        /// The argument of the SMTP `RCPT TO` command.
+        &smtp_to;
+
+        /// The IP address of the client in text form.
+        addr_text?;
+    }
+
+    flag! {
+        ssl;
+        blocked;
+        quit;
+        starttls;
+        esmtp;
+        auth_wait;
+    }
+
+    /// The mail protocol of the session.
+    pub fn protocol(&self) -> Protocol {
+        Protocol::from(unsafe { self.as_raw().protocol() })
+    }
+
+    /// The authentication method chosen by the client.
+    pub fn auth_method(&self) -> AuthMethod {
+        AuthMethod::from(unsafe { self.as_raw().auth_method() })
+    }
+
+    /// Passes the authenticated session to the backend server at `addr`.
+    pub fn proxy_init(&self, addr: SocketAddr) -> Result<(), Error> {
+        let pool = self.connection().pool();
+        let name = pool.strdup(addr.to_string()).ok_or(Error::OutOfMemory)?;
+        let (sockaddr, socklen) = alloc_sockaddr(pool, addr).ok_or(Error::OutOfMemory)?;
+        let peer = pool.calloc::<ffi::ngx_addr_t>().ok_or(Error::OutOfMemory)?;
+
+        peer.sockaddr = sockaddr;
+        peer.socklen = socklen as ffi::socklen_t;
+        peer.name = name.into();
+
+        unsafe { ffi::ngx_mail_proxy_init(self.as_ptr(), peer) };
+
+        Ok(())
+    }
+
+    /// Sends the protocol specific "internal server error" response and closes the session.
+    pub fn internal_server_error(&self) {
+        unsafe { ffi::ngx_mail_session_internal_server_error(self.as_ptr()) }
+    }
+
+    /// Closes the client connection of the session.
+    pub fn close(&self) {
+        unsafe { ffi::ngx_mail_close_connection(self.connection().as_ptr()) }
+    }
+}
+
+impl AsRef<LogRef> for SessionRef {
+    fn as_ref(&self) -> &LogRef {
+        self.connection().log()
+    }
+}
+
+/// The mail protocol of the session.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive)]
+pub enum Protocol {
+    #[default]
+    Pop3 = ffi::NGX_MAIL_POP3_PROTOCOL,
+    Imap = ffi::NGX_MAIL_IMAP_PROTOCOL,
+    Smtp = ffi::NGX_MAIL_SMTP_PROTOCOL,
+}
+
+/// The authentication method of the session.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive)]
+pub enum AuthMethod {
+    #[default]
+    Plain = ffi::NGX_MAIL_AUTH_PLAIN,
+    Login = ffi::NGX_MAIL_AUTH_LOGIN,
+    LoginUsername = ffi::NGX_MAIL_AUTH_LOGIN_USERNAME,
+    Apop = ffi::NGX_MAIL_AUTH_APOP,
+    CramMd5 = ffi::NGX_MAIL_AUTH_CRAM_MD5,
+    External = ffi::NGX_MAIL_AUTH_EXTERNAL,
+    None = ffi::NGX_MAIL_AUTH_NONE,
+}
+
+pub trait ModuleContext {
+    /// Returns the module's context
+    fn module_ctx<T>(&self, m: &ModuleRef) -> Option<&T>;
+
+    /// Returns the module's context
+    #[allow(clippy::mut_from_ref)]
+    fn module_ctx_mut<T>(&self, m: &ModuleRef) -> Option<&mut T>;
+
+    /// Sets the module's context
+    fn set_module_ctx<T>(&self, m: &ModuleRef, ctx: &T);
+}
+
+impl<M> ModuleContext for M
+where
+    M: UnsafeModuleContext,
+{
+    fn module_ctx<T>(&self, m: &ModuleRef) -> Option<&T> {
+        unsafe { self.unchecked_module_ctx(m.ctx_index()).map(|p| p.as_ref()) }
+    }
+
+    fn module_ctx_mut<T>(&self, m: &ModuleRef) -> Option<&mut T> {
+        unsafe {
+            self.unchecked_module_ctx(m.ctx_index())
+                .map(|mut p| p.as_mut())
+        }
+    }
+
+    fn set_module_ctx<T>(&self, m: &ModuleRef, ctx: &T) {
+        unsafe {
+            self.unchecked_set_module_ctx(
+                m.ctx_index(),
+                NonNull::new_unchecked(ctx as *const _ as *mut T),
+            );
+        }
+    }
+}
+
+pub trait UnsafeModuleContext {
+    /// Returns the module's context
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that `idx` is within the bounds of the `ctx` array.
+    unsafe fn unchecked_module_ctx<T>(&self, idx: usize) -> Option<NonNull<T>>;
+
+    /// Sets the module's context
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that `idx` is within the bounds of the `ctx` array.
+    unsafe fn unchecked_set_module_ctx<T>(&self, idx: usize, ctx: NonNull<T>);
+}
+
+impl UnsafeModuleContext for SessionRef {
+    unsafe fn unchecked_module_ctx<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().ctx.add(idx).read().cast())
+    }
+
+    unsafe fn unchecked_set_module_ctx<T>(&self, idx: usize, ctx: NonNull<T>) {
+        self.as_raw().ctx.add(idx).write(ctx.as_ptr().cast());
+    }
+}
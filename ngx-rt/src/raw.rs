@@ -199,7 +199,7 @@ impl AsResult for *mut c_char {
     {
         self.ok_or_else(|err| {
             Error::ConfigError(if err == NGX_CONF_ERROR {
-                CString::new("error").unwrap()
+                CString::default()
             } else {
                 unsafe { CStr::from_ptr(self as *const _).to_owned() }
             })
@@ -0,0 +1,103 @@
+use foreign_types::{foreign_type, ForeignTypeRef};
+use num_enum::FromPrimitive;
+
+use crate::{
+    core::{ArrayRef, Code},
+    ffi, never_drop,
+    stream::HandlerFn,
+    AsRawMut, AsRawRef, NativeCallback,
+};
+
+foreign_type! {
+    pub unsafe type MainConf: Send {
+        type CType = ffi::ngx_stream_core_main_conf_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_core_main_conf_t>;
+    }
+}
+
+impl MainConfRef {
+    pub fn phases(&self, p: Phases) -> &PhaseRef {
+        unsafe { PhaseRef::from_ptr(&self.as_raw().phases[p as usize] as *const _ as *mut _) }
+    }
+
+    pub fn phases_mut(&mut self, p: Phases) -> &mut PhaseRef {
+        unsafe { PhaseRef::from_ptr_mut(&mut self.as_raw_mut().phases[p as usize] as *mut _) }
+    }
+
+    /// Adds the handler to the phase.
+    ///
+    /// Returns [`Code::ERROR`] for [`Phases::Content`], which has no handlers array,
+    /// the content handler is set with [`SrvConfRef::set_handler`](super::SrvConfRef::set_handler).
+    pub fn push_handler(
+        &mut self,
+        p: Phases,
+        h: <HandlerFn as NativeCallback>::CType,
+    ) -> Result<(), Code> {
+        if p == Phases::Content {
+            return Err(Code::ERROR);
+        }
+
+        self.phases_mut(p)
+            .handlers_mut()
+            .push(Some(h))
+            .map(|_| ())
+            .ok_or(Code::ERROR)
+    }
+}
+
+foreign_type! {
+    pub unsafe type Phase: Send {
+        type CType = ffi::ngx_stream_phase_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_phase_t>;
+    }
+}
+
+impl PhaseRef {
+    pub fn handlers(&self) -> &ArrayRef<Option<HandlerFn>> {
+        unsafe { ArrayRef::from_ptr(&self.as_raw().handlers as *const _ as *mut _) }
+    }
+
+    pub fn handlers_mut(&mut self) -> &mut ArrayRef<ffi::ngx_stream_handler_pt> {
+        unsafe { ArrayRef::from_ptr_mut(&mut self.as_raw_mut().handlers as *mut _) }
+    }
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive)]
+pub enum Phases {
+    #[default]
+    PostAccept = ffi::ngx_stream_phases_NGX_STREAM_POST_ACCEPT_PHASE,
+
+    Preaccess = ffi::ngx_stream_phases_NGX_STREAM_PREACCESS_PHASE,
+    Access = ffi::ngx_stream_phases_NGX_STREAM_ACCESS_PHASE,
+    Ssl = ffi::ngx_stream_phases_NGX_STREAM_SSL_PHASE,
+    Preread = ffi::ngx_stream_phases_NGX_STREAM_PREREAD_PHASE,
+    Content = ffi::ngx_stream_phases_NGX_STREAM_CONTENT_PHASE,
+
+    Log = ffi::ngx_stream_phases_NGX_STREAM_LOG_PHASE,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::zeroed;
+
+    use super::*;
+
+    #[test]
+    fn push_content_handler() {
+        unsafe extern "C" fn handler(_: *mut ffi::ngx_stream_session_t) -> ffi::ngx_int_t {
+            ffi::NGX_DECLINED as _
+        }
+
+        let mut cmcf: ffi::ngx_stream_core_main_conf_t = unsafe { zeroed() };
+        let cmcf = unsafe { MainConfRef::from_ptr_mut(&mut cmcf) };
+
+        assert_eq!(
+            cmcf.push_handler(Phases::Content, handler),
+            Err(Code::ERROR)
+        );
+        assert!(cmcf.phases(Phases::Content).handlers().is_empty());
+    }
+}
@@ -0,0 +1,7 @@
+mod main;
+mod module;
+mod srv;
+
+pub use self::main::{MainConfRef, PhaseRef, Phases};
+pub use self::module::{main_conf, main_conf_mut, module, srv_conf, srv_conf_mut};
+pub use self::srv::SrvConfRef;
@@ -0,0 +1,42 @@
+use foreign_types::ForeignTypeRef;
+
+use crate::{
+    core::ModuleRef,
+    ffi,
+    stream::{
+        core::{MainConfRef, SrvConfRef},
+        MainConf, SrvConf,
+    },
+};
+
+pub fn module() -> &'static ModuleRef {
+    unsafe { ModuleRef::from_ptr(&mut ffi::ngx_stream_core_module as *mut _) }
+}
+
+pub fn main_conf<T>(cf: &T) -> Option<&MainConfRef>
+where
+    T: MainConf,
+{
+    cf.main_conf(module())
+}
+
+pub fn main_conf_mut<T>(cf: &T) -> Option<&mut MainConfRef>
+where
+    T: MainConf,
+{
+    cf.main_conf_mut(module())
+}
+
+pub fn srv_conf<T>(cf: &T) -> Option<&SrvConfRef>
+where
+    T: SrvConf,
+{
+    cf.srv_conf(module())
+}
+
+pub fn srv_conf_mut<T>(cf: &T) -> Option<&mut SrvConfRef>
+where
+    T: SrvConf,
+{
+    cf.srv_conf_mut(module())
+}
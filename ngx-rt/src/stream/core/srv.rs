@@ -0,0 +1,37 @@
+use foreign_types::foreign_type;
+
+use crate::{core::MSec, ffi, never_drop, stream::ContentHandlerFn, AsRawMut, NativeCallback};
+
+foreign_type! {
+    pub unsafe type SrvConf: Send {
+        type CType = ffi::ngx_stream_core_srv_conf_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_core_srv_conf_t>;
+    }
+}
+
+impl SrvConfRef {
+    property! {
+        /// The size of the preread buffer.
+        preread_buffer_size: usize;
+
+        /// The timeout of the preread phase.
+        preread_timeout into MSec;
+
+        resolver_timeout into MSec;
+        proxy_protocol_timeout into MSec;
+
+        tcp_nodelay: isize;
+    }
+
+    callback! {
+        /// The content handler which owns the client connection of the server.
+        handler: ContentHandlerFn;
+    }
+
+    /// Sets the content handler of the server.
+    pub fn set_handler(&mut self, h: <ContentHandlerFn as NativeCallback>::CType) -> &mut Self {
+        unsafe { self.as_raw_mut().handler = Some(h) }
+        self
+    }
+}
@@ -1 +1,22 @@
+mod conf;
+pub mod core;
+#[macro_use]
+mod log;
+mod module;
+mod preread;
+pub mod script;
+mod session;
+pub mod upstream;
+#[macro_use]
+pub mod var;
 
+pub use self::conf::{
+    Context as ConfContext, ContextRef as ConfContextRef, MainConf, SrvConf, UnsafeMainConf,
+    UnsafeSrvConf,
+};
+pub use self::module::{conf_ctx, main_conf, module};
+pub use self::preread::Preread;
+pub use self::session::{
+    ContentHandlerFn, HandlerFn, ModuleContext, Session, SessionRef, Status, UnsafeModuleContext,
+};
+pub use self::var::{RawVar, Var, VarRef};
@@ -0,0 +1,74 @@
+use std::slice;
+
+use crate::{
+    core::{Code, MSec},
+    stream::{core::srv_conf, SessionRef, Status},
+    AsRawRef,
+};
+
+/// The result of a preread phase handler.
+///
+/// The preread phase reads the client data into the connection buffer,
+/// up to `preread_buffer_size` bytes and until `preread_timeout` expires,
+/// and calls the handlers each time new data arrives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preread {
+    /// The data was recognized, continue with the next phase.
+    Ok,
+
+    /// The data is not handled by the module, call the next handler.
+    Declined,
+
+    /// More data is needed to make a decision.
+    Again,
+
+    /// Finalize the session with the status.
+    Finalize(Status),
+}
+
+impl From<Preread> for isize {
+    fn from(r: Preread) -> Self {
+        match r {
+            Preread::Ok => Code::OK.into(),
+            Preread::Declined => Code::DECLINED.into(),
+            Preread::Again => Code::AGAIN.into(),
+            Preread::Finalize(status) => status as isize,
+        }
+    }
+}
+
+impl SessionRef {
+    /// Returns the client data buffered by the preread phase so far.
+    ///
+    /// The data stays in the buffer and will be passed to the content handler.
+    pub fn preread(&self) -> &[u8] {
+        unsafe {
+            let c = self.connection().as_raw();
+
+            match c.buffer.as_ref() {
+                Some(b) if !b.pos.is_null() && b.last > b.pos => {
+                    slice::from_raw_parts(b.pos, b.last.offset_from(b.pos) as usize)
+                }
+                _ => &[],
+            }
+        }
+    }
+
+    /// Returns `true` if the preread buffer has no room for more data.
+    ///
+    /// nginx fails the session with `400` if a handler returns [`Preread::Again`] on a full buffer.
+    pub fn preread_full(&self) -> bool {
+        unsafe {
+            self.connection()
+                .as_raw()
+                .buffer
+                .as_ref()
+                .map_or(false, |b| b.last == b.end)
+        }
+    }
+
+    /// Returns the `preread_buffer_size` and `preread_timeout` of the server.
+    pub fn preread_limits(&self) -> Option<(usize, MSec)> {
+        srv_conf(self).map(|conf| (conf.preread_buffer_size(), conf.preread_timeout()))
+    }
+}
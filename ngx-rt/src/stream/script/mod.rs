@@ -0,0 +1,3 @@
+mod value;
+
+pub use self::value::{Compiler as ComplexValueCompiler, ComplexValue, ComplexValueRef};
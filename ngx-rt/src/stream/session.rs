@@ -0,0 +1,173 @@
+use std::ptr::NonNull;
+
+use foreign_types::{foreign_type, ForeignTypeRef};
+use num_enum::FromPrimitive;
+
+use crate::{
+    core::{ArrayRef, ConnRef, LogRef, ModuleRef},
+    ffi, native_callback, never_drop, AsRawRef, Error, FromRawRef,
+};
+
+use super::{
+    upstream::{StateRef, UpstreamRef},
+    UnsafeMainConf, UnsafeSrvConf,
+};
+
+foreign_type! {
+    pub unsafe type Session: Send {
+        type CType = ffi::ngx_stream_session_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_session_t>;
+    }
+}
+
+impl UnsafeMainConf for SessionRef {
+    unsafe fn unchecked_main_conf<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().main_conf.add(idx).read().cast())
+    }
+}
+
+impl UnsafeSrvConf for SessionRef {
+    unsafe fn unchecked_srv_conf<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().srv_conf.add(idx).read().cast())
+    }
+}
+
+impl SessionRef {
+    property! {
+        /// client connection
+        connection: &ConnRef;
+
+        /// Session upstream object for proxying.
+        upstream as &mut UpstreamRef;
+
+        /// The number of bytes received from the client.
+        received: i64;
+
+        start_sec: i64;
+        start_msec: usize;
+
+        /// The session status, used as `$status` variable.
+        status: usize { get; set; };
+
+        phase_handler: isize;
+
+        limit_conn_status(): u32;
+    }
+
+    flag! {
+        ssl;
+        stat_processing;
+        health_check;
+    }
+
+    /// The session was accepted on a datagram (UDP) socket.
+    pub fn is_udp(&self) -> bool {
+        self.connection().ty().is_dgram()
+    }
+
+    /// The states of the upstream attempts made for this session.
+    pub fn upstream_states(&self) -> impl Iterator<Item = &StateRef> {
+        unsafe {
+            ArrayRef::<ffi::ngx_stream_upstream_state_t>::from_raw(self.as_raw().upstream_states)
+                .into_iter()
+                .flat_map(|a| a.iter())
+                .map(|s| StateRef::from_ptr(s as *const _ as *mut _))
+        }
+    }
+
+    /// Finalizes the session with the `status` and closes the client connection.
+    pub fn finalize(&self, status: Status) {
+        unsafe { ffi::ngx_stream_finalize_session(self.as_ptr(), status as usize) }
+    }
+}
+
+impl AsRef<LogRef> for SessionRef {
+    fn as_ref(&self) -> &LogRef {
+        self.connection().log()
+    }
+}
+
+#[native_callback]
+pub type HandlerFn = fn(s: &SessionRef) -> Result<(), Error>;
+
+#[native_callback]
+pub type ContentHandlerFn = fn(s: &SessionRef);
+
+/// The status of the stream session.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive)]
+pub enum Status {
+    #[default]
+    Ok = ffi::NGX_STREAM_OK,
+    BadRequest = ffi::NGX_STREAM_BAD_REQUEST,
+    Forbidden = ffi::NGX_STREAM_FORBIDDEN,
+    InternalServerError = ffi::NGX_STREAM_INTERNAL_SERVER_ERROR,
+    BadGateway = ffi::NGX_STREAM_BAD_GATEWAY,
+    ServiceUnavailable = ffi::NGX_STREAM_SERVICE_UNAVAILABLE,
+}
+
+pub trait ModuleContext {
+    /// Returns the module's context
+    fn module_ctx<T>(&self, m: &ModuleRef) -> Option<&T>;
+
+    /// Returns the module's context
+    #[allow(clippy::mut_from_ref)]
+    fn module_ctx_mut<T>(&self, m: &ModuleRef) -> Option<&mut T>;
+
+    /// Sets the module's context
+    fn set_module_ctx<T>(&self, m: &ModuleRef, ctx: &T);
+}
+
+impl<M> ModuleContext for M
+where
+    M: UnsafeModuleContext,
+{
+    fn module_ctx<T>(&self, m: &ModuleRef) -> Option<&T> {
+        unsafe { self.unchecked_module_ctx(m.ctx_index()).map(|p| p.as_ref()) }
+    }
+
+    fn module_ctx_mut<T>(&self, m: &ModuleRef) -> Option<&mut T> {
+        unsafe {
+            self.unchecked_module_ctx(m.ctx_index())
+                .map(|mut p| p.as_mut())
+        }
+    }
+
+    fn set_module_ctx<T>(&self, m: &ModuleRef, ctx: &T) {
+        unsafe {
+            self.unchecked_set_module_ctx(
+                m.ctx_index(),
+                NonNull::new_unchecked(ctx as *const _ as *mut T),
+            );
+        }
+    }
+}
+
+pub trait UnsafeModuleContext {
+    /// Returns the module's context
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that `idx` is within the bounds of the `ctx` array.
+    unsafe fn unchecked_module_ctx<T>(&self, idx: usize) -> Option<NonNull<T>>;
+
+    /// Sets the module's context
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it dereferences raw pointers.
+    /// The caller must ensure that `idx` is within the bounds of the `ctx` array.
+    unsafe fn unchecked_set_module_ctx<T>(&self, idx: usize, ctx: NonNull<T>);
+}
+
+impl UnsafeModuleContext for SessionRef {
+    unsafe fn unchecked_module_ctx<T>(&self, idx: usize) -> Option<NonNull<T>> {
+        NonNull::new(self.as_raw().ctx.add(idx).read().cast())
+    }
+
+    unsafe fn unchecked_set_module_ctx<T>(&self, idx: usize, ctx: NonNull<T>) {
+        self.as_raw().ctx.add(idx).write(ctx.as_ptr().cast());
+    }
+}
@@ -0,0 +1,12 @@
+mod conf;
+mod module;
+mod peer;
+mod state;
+#[allow(clippy::module_inception)]
+mod upstream;
+
+pub use self::conf::{MainConf, MainConfRef, SrvConf, SrvConfRef};
+pub use self::module::{main_conf, main_conf_mut, module, srv_conf, srv_conf_mut};
+pub use self::peer::{InitFn, InitPeerFn, Peer, PeerRef};
+pub use self::state::{State, StateRef};
+pub use self::upstream::{Upstream, UpstreamRef};
@@ -0,0 +1,37 @@
+use foreign_types::foreign_type;
+
+use crate::{core::MSec, ffi, never_drop};
+
+foreign_type! {
+    pub unsafe type State: Send {
+        type CType = ffi::ngx_stream_upstream_state_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_upstream_state_t>;
+    }
+}
+
+impl StateRef {
+    property! {
+        /// Time spent on the upstream connection.
+        response_time into MSec;
+
+        /// Time spent on establishing a connection with the upstream server.
+        ///
+        /// It is unset if the connection has not been established yet.
+        connect_time into MSec;
+
+        /// Time spent on receiving the first byte from the upstream server.
+        first_byte_time into MSec;
+
+        /// The number of bytes received from the upstream server.
+        bytes_received: i64;
+
+        /// The number of bytes sent to the upstream server.
+        bytes_sent: i64;
+    }
+
+    str! {
+        /// The name of the upstream peer.
+        peer?;
+    }
+}
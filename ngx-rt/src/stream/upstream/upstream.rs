@@ -0,0 +1,60 @@
+use foreign_types::foreign_type;
+
+use crate::{
+    core::{BufRef, MSec},
+    event::PeerConnRef,
+    ffi, never_drop,
+};
+
+use super::{SrvConfRef, StateRef};
+
+foreign_type! {
+    pub unsafe type Upstream: Send {
+        type CType = ffi::ngx_stream_upstream_t;
+
+        fn drop = never_drop::<ffi::ngx_stream_upstream_t>;
+    }
+}
+
+impl UpstreamRef {
+    property! {
+        &mut peer: &mut PeerConnRef;
+
+        /// The buffer for the data read from the client.
+        &mut downstream_buf: &mut BufRef;
+
+        /// The buffer for the data read from the upstream server.
+        &mut upstream_buf: &mut BufRef;
+
+        /// The number of bytes received from the upstream server.
+        received: i64;
+
+        /// The number of datagrams sent to the upstream server.
+        requests: usize;
+
+        /// The number of datagrams received from the upstream server.
+        responses: usize;
+
+        /// The state of the current upstream attempt.
+        state as &mut StateRef;
+
+        /// The upstream configuration used by this session.
+        upstream as &SrvConfRef;
+
+        /// The time when the upstream connection was started.
+        start_time into MSec;
+
+        upload_rate: usize;
+        download_rate: usize;
+    }
+
+    str! {
+        &ssl_name;
+    }
+
+    flag! {
+        connected;
+        proxy_protocol;
+        half_closed;
+    }
+}
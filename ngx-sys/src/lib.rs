@@ -25,3 +25,11 @@ cfg_if::cfg_if! {
 /// help: within `ngx_core_module_t`, the trait `Sync` is not implemented for `*mut u8`
 /// note: required because it appears within the type `ngx_str_t`
 unsafe impl Sync for ngx_str_t {}
+
+/// `ngx_mail_module_t` holds a raw `protocol` pointer, which is always null for the Rust modules.
+#[cfg(feature = "mail")]
+unsafe impl Sync for ngx_mail_module_t {}
+
+/// `ngx_event_module_t` holds a raw pointer to the static module name.
+#[cfg(feature = "event")]
+unsafe impl Sync for ngx_event_module_t {}
@@ -0,0 +1,176 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use object::{Object, ObjectSection, ObjectSymbol};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, trace};
+
+use crate::{is_nginx_module, Format};
+
+/// The suffix of the symbol which `#[derive(Module)]` exports with the directives in JSON lines.
+const DOC_SYMBOL_SUFFIX: &str = "_module_doc";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Module {
+    pub module: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default)]
+    pub directives: Vec<Directive>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Directive {
+    pub name: String,
+    pub syntax: String,
+    pub default: Option<String>,
+    pub context: Vec<String>,
+    pub doc: String,
+}
+
+/// Extracts the modules and their directives from the built modules in the directory,
+/// only from the named targets if `names` is not empty.
+#[instrument]
+pub fn extract_modules(dir: &Path, names: &[String]) -> anyhow::Result<Vec<Module>> {
+    let mut files = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && is_nginx_module(p))
+        .filter(|p| names.is_empty() || names.iter().any(|name| is_target(p, name)))
+        .collect::<Vec<_>>();
+
+    files.sort();
+
+    let mut modules = vec![];
+
+    for file in files {
+        trace!(?file, "extract module");
+
+        let data = fs::read(&file)?;
+        let obj = object::File::parse(&*data)
+            .with_context(|| format!("parse module `{}`", file.display()))?;
+
+        for doc in find_docs(&obj)? {
+            modules.push(parse_doc(doc)?);
+        }
+    }
+
+    info!(modules = modules.len(), "extracted");
+
+    Ok(modules)
+}
+
+/// Returns `true` if the file is built from the target, e.g. `libfoo.so` from `foo`.
+fn is_target(file: &Path, name: &str) -> bool {
+    let name = name.replace('-', "_");
+
+    file.file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .is_some_and(|stem| stem == name || stem.strip_prefix("lib") == Some(name.as_str()))
+}
+
+fn find_docs<'data>(obj: &object::File<'data>) -> anyhow::Result<Vec<&'data [u8]>> {
+    let mut docs = vec![];
+
+    for sym in obj.symbols().chain(obj.dynamic_symbols()) {
+        let Ok(name) = sym.name() else {
+            continue;
+        };
+
+        if !name.trim_start_matches('_').ends_with(DOC_SYMBOL_SUFFIX) || sym.is_undefined() {
+            continue;
+        }
+
+        let Some(section) = sym
+            .section_index()
+            .and_then(|idx| obj.section_by_index(idx).ok())
+        else {
+            continue;
+        };
+
+        match section.data_range(sym.address(), sym.size())? {
+            Some(data) if !docs.contains(&data) => docs.push(data),
+            Some(_) => {}
+            None => bail!("missing data of symbol `{}`", name),
+        }
+    }
+
+    Ok(docs)
+}
+
+/// Parses the module and its directives, which are described in JSON, one object per line.
+fn parse_doc(doc: &[u8]) -> anyhow::Result<Module> {
+    let mut lines = doc.split(|&b| b == b'\n').filter(|line| !line.is_empty());
+
+    let Some(line) = lines.next() else {
+        bail!("empty module doc")
+    };
+    let mut module: Module = serde_json::from_slice(line)?;
+
+    for line in lines {
+        module.directives.push(serde_json::from_slice(line)?);
+    }
+
+    Ok(module)
+}
+
+/// Writes the modules in the format to the directory, or stdout if not specified.
+pub fn write(modules: &[Module], format: Format, output: Option<&Path>) -> anyhow::Result<()> {
+    match (format, output) {
+        (Format::Json, None) => println!("{}", serde_json::to_string_pretty(modules)?),
+        (Format::Json, Some(dir)) => {
+            fs::create_dir_all(dir)?;
+            fs::write(
+                dir.join("modules.json"),
+                serde_json::to_string_pretty(modules)?,
+            )?;
+        }
+        (Format::Markdown, None) => {
+            for module in modules {
+                println!("{}", to_markdown(module));
+            }
+        }
+        (Format::Markdown, Some(dir)) => {
+            fs::create_dir_all(dir)?;
+
+            for module in modules {
+                let file = dir.join(format!("{}.md", module.module));
+
+                info!(?file, "write module reference");
+
+                fs::write(file, to_markdown(module))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders the directive reference of the module like nginx.org.
+fn to_markdown(module: &Module) -> String {
+    let mut s = String::new();
+
+    let _ = writeln!(s, "# Module {}\n", module.module);
+    let _ = writeln!(s, "## Directives");
+
+    for directive in &module.directives {
+        let _ = writeln!(s, "\n### {}\n", directive.name);
+        let _ = writeln!(s, "```");
+        let _ = writeln!(s, "Syntax:  {}", directive.syntax);
+        let _ = writeln!(
+            s,
+            "Default: {}",
+            directive.default.as_deref().unwrap_or("—")
+        );
+        let _ = writeln!(s, "Context: {}", directive.context.join(", "));
+        let _ = writeln!(s, "```");
+
+        if !directive.doc.is_empty() {
+            let _ = writeln!(s, "\n{}", directive.doc);
+        }
+    }
+
+    s
+}
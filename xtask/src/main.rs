@@ -7,10 +7,12 @@ use std::process::Command;
 
 use anyhow::bail;
 use cargo_metadata::{Metadata, MetadataCommand};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use fs_extra::dir::{copy, CopyOptions};
 use tracing::{debug, info, instrument, trace};
 
+mod doc;
+
 /// CI/CD workflows
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -59,6 +61,37 @@ enum Cmd {
     Build,
     /// Test the runtime with static link
     Test,
+    /// Generate the directive reference of the modules,
+    /// built from all examples of the package if neither `--example` nor `--lib` is specified
+    Doc {
+        /// Package to build
+        #[arg(long, short, default_value = "ngx-mod")]
+        package: String,
+
+        /// Build only the specified example
+        #[arg(long)]
+        example: Vec<String>,
+
+        /// Build only the library of the package
+        #[arg(long)]
+        lib: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+
+        /// Output directory, print to stdout if not specified
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Machine-readable description in JSON
+    Json,
+    /// nginx.org-style reference in Markdown
+    Markdown,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -119,6 +152,49 @@ fn main() -> anyhow::Result<()> {
 
             let status = cmd.spawn()?.wait()?;
         }
+        Cmd::Doc {
+            ref package,
+            ref example,
+            lib,
+            format,
+            ref output,
+        } => {
+            let all_examples = example.is_empty() && !lib;
+            let cmd = cargo.args(["build", "--package", package.as_str()]);
+
+            if lib {
+                cmd.arg("--lib");
+            }
+            for name in example {
+                cmd.args(["--example", name.as_str()]);
+            }
+            if all_examples {
+                cmd.arg("--examples");
+            }
+
+            debug!(?cmd, "doc");
+
+            let status = cmd.spawn()?.wait()?;
+
+            if status.success() {
+                let mut modules = vec![];
+
+                if lib {
+                    modules.extend(doc::extract_modules(&profile_dir, &[package.clone()])?);
+                }
+                if all_examples || !example.is_empty() {
+                    let examples_dir = profile_dir.join("examples");
+
+                    modules.extend(doc::extract_modules(&examples_dir, example)?);
+                }
+
+                modules.sort_by(|lhs, rhs| lhs.module.cmp(&rhs.module));
+
+                doc::write(&modules, format, output.as_deref())?;
+            } else {
+                bail!("build modules failed");
+            }
+        }
     }
 
     Ok(())
@@ -224,7 +300,7 @@ fn copy_nginx_modules(from: &Path, to: &Path) -> io::Result<()> {
     Ok(())
 }
 
-fn is_nginx_module(file: &Path) -> bool {
+pub(crate) fn is_nginx_module(file: &Path) -> bool {
     if let Some((name, ext)) = file.file_name().zip(file.extension()) {
         if !name.to_string_lossy().contains('-') && (ext == "so" || ext == "dylib" || ext == "dll")
         {